@@ -0,0 +1,79 @@
+// 大号数字字形，每个 5 行，'#' 为实心
+const GLYPH_HEIGHT: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum BigTarget {
+    Pomodoro,
+    Countdown,
+}
+
+fn glyph(symbol: char) -> Option<[&'static str; GLYPH_HEIGHT]> {
+    Some(match symbol {
+        '0' => ["###", "# #", "# #", "# #", "###"],
+        '1' => [" # ", "## ", " # ", " # ", "###"],
+        '2' => ["###", "  #", "###", "#  ", "###"],
+        '3' => ["###", "  #", "###", "  #", "###"],
+        '4' => ["# #", "# #", "###", "  #", "  #"],
+        '5' => ["###", "#  ", "###", "  #", "###"],
+        '6' => ["###", "#  ", "###", "# #", "###"],
+        '7' => ["###", "  #", "  #", "  #", "  #"],
+        '8' => ["###", "# #", "###", "# #", "###"],
+        '9' => ["###", "# #", "###", "  #", "###"],
+        ':' => [" ", "#", " ", "#", " "],
+        '.' => [" ", " ", " ", " ", "#"],
+        '-' => ["   ", "   ", "###", "   ", "   "],
+        'd' => ["  #", "  #", "###", "# #", "###"],
+        ' ' => [" ", " ", " ", " ", " "],
+        _ => return None,
+    })
+}
+
+// 未缩放时的宽度，字形之间空一列
+fn base_width(text: &str) -> usize {
+    let widths: Vec<usize> = text
+        .chars()
+        .filter_map(glyph)
+        .map(|rows| rows[0].len())
+        .collect();
+    widths.iter().sum::<usize>() + widths.len().saturating_sub(1)
+}
+
+// 在给定区域内能放下的最大缩放倍数（横向, 纵向），放不下返回 None
+pub fn fit(text: &str, width: usize, height: usize) -> Option<(usize, usize)> {
+    let base = base_width(text);
+    if base == 0 {
+        return None;
+    }
+    let horizontal = width / base;
+    // 字符格大约是高的两倍，纵向倍数取横向的一半
+    let vertical = (horizontal / 2).max(1).min(height / GLYPH_HEIGHT);
+    (horizontal >= 1 && vertical >= 1).then_some((horizontal, vertical))
+}
+
+pub fn render(text: &str, horizontal: usize, vertical: usize) -> Vec<String> {
+    let glyphs: Vec<[&str; GLYPH_HEIGHT]> = text.chars().filter_map(glyph).collect();
+    let mut lines = Vec::with_capacity(GLYPH_HEIGHT * vertical);
+    for row in 0..GLYPH_HEIGHT {
+        let line = glyphs
+            .iter()
+            .map(|rows| {
+                rows[row]
+                    .chars()
+                    .map(|pixel| {
+                        let fill = if pixel == '#' { "█" } else { " " };
+                        fill.repeat(horizontal)
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join(&" ".repeat(horizontal));
+        for _ in 0..vertical {
+            lines.push(line.clone());
+        }
+    }
+    lines
+}
+
+pub fn rendered_height(vertical: usize) -> usize {
+    GLYPH_HEIGHT * vertical
+}
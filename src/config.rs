@@ -1,73 +1,946 @@
-use std::{io::Read, sync::Arc};
+use std::fmt;
+use std::sync::Arc;
+
+use chrono::{
+    DateTime, Datelike, Days, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+use serde_derive::{Deserialize, Serialize};
+use tokio::sync::{watch, Mutex};
+
+use crate::i18n::{self, Lang};
+use crate::recurrence::{Milestone, Repeat};
+use crate::template;
+
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
 
-use serde_derive::Deserialize;
-use tokio::sync::Mutex;
 pub trait HotReload {
-    async fn reload(&mut self) -> Result<(), Box<dyn std::error::Error>>;
+    async fn reload(&mut self) -> Result<(), ConfigError>;
+}
+
+// 某个倒计时条目的错误，index 从 0 开始
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryError {
+    pub index: usize,
+    pub title: String,
+    pub message: String,
+}
+
+// 加载配置失败的原因，有问题的设置和倒计时逐条列出
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    Io(String),
+    Parse(String),
+    Invalid {
+        settings: Vec<String>,
+        entries: Vec<EntryError>,
+    },
+}
+
+impl ConfigError {
+    pub fn lines(&self) -> Vec<String> {
+        match self {
+            ConfigError::Io(message) | ConfigError::Parse(message) => vec![message.clone()],
+            ConfigError::Invalid { settings, entries } => settings
+                .iter()
+                .cloned()
+                .chain(entries.iter().map(|error| {
+                    i18n::tf(
+                        "config.entry_error",
+                        &[("index", &(error.index + 1)), ("message", &error.message)],
+                    )
+                }))
+                .collect(),
+        }
+    }
 }
 
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.lines().join("\n"))
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Countdown {
     pub title: String,
     pub datetime: String,
     #[serde(default = "default_enabled")]
     pub enabled: bool,
+    #[serde(default)]
+    pub repeat: Option<String>,
+    #[serde(default)]
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub remind: Option<Vec<String>>,
+    // 进度条的起点，例如入职日期
+    #[serde(default)]
+    pub start: Option<String>,
+    #[serde(default)]
+    pub format: Option<FormatConfig>,
+    #[serde(default)]
+    pub mode: Mode,
+    // 正计时经过这些时长时发通知，例如 "every 1000 days"、"every year"
+    #[serde(default)]
+    pub milestones: Vec<String>,
+}
+
+// 倒计时到 datetime，或者从 datetime 开始正计时（纪念日之类）
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    #[default]
+    Countdown,
+    Countup,
 }
 
 fn default_enabled() -> bool {
     true
 }
 
+#[derive(Debug, Clone, Copy)]
+pub enum EventZone {
+    Local,
+    Fixed(FixedOffset),
+    Named(Tz),
+}
+
+impl EventZone {
+    pub fn is_local(&self) -> bool {
+        matches!(self, EventZone::Local)
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            EventZone::Local => "local".to_string(),
+            EventZone::Fixed(offset) => offset.to_string(),
+            EventZone::Named(tz) => tz.name().to_string(),
+        }
+    }
+
+    pub fn instant_of(&self, naive: NaiveDateTime) -> Option<DateTime<Utc>> {
+        fn resolve<Z: TimeZone>(zone: &Z, naive: NaiveDateTime) -> Option<DateTime<Utc>> {
+            // 夏令时跳过的那一小时不存在，顺延一小时
+            zone.from_local_datetime(&naive)
+                .earliest()
+                .or_else(|| {
+                    zone.from_local_datetime(&(naive + Duration::hours(1)))
+                        .earliest()
+                })
+                .map(|datetime| datetime.with_timezone(&Utc))
+        }
+        match self {
+            EventZone::Local => resolve(&Local, naive),
+            EventZone::Fixed(offset) => resolve(offset, naive),
+            EventZone::Named(tz) => resolve(tz, naive),
+        }
+    }
+
+    pub fn wall_time_of(&self, datetime: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            EventZone::Local => datetime.with_timezone(&Local).naive_local(),
+            EventZone::Fixed(offset) => datetime.with_timezone(offset).naive_local(),
+            EventZone::Named(tz) => datetime.with_timezone(tz).naive_local(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Target {
+    pub at: DateTime<Utc>,
+    // 重复事件上一次已经到点的时间
+    pub previous: Option<DateTime<Utc>>,
+    pub start: Option<DateTime<Utc>>,
+    pub zone: EventZone,
+}
+
+impl Target {
+    // 从起点到目标时间已经走过的比例，没有起点时重复事件从上一次发生算起
+    pub fn progress(&self, now: DateTime<Utc>) -> Option<f64> {
+        let start = self.start.or(self.previous)?;
+        let total = (self.at - start).num_milliseconds();
+        if total <= 0 {
+            return Some(1.0);
+        }
+        let elapsed = (now - start).num_milliseconds();
+        Some((elapsed as f64 / total as f64).clamp(0.0, 1.0))
+    }
+}
+
+// 加载时解析好的倒计时，调度和渲染时不再解析字符串
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub title: String,
+    // 事件时区里的墙上时间，重复事件从这里开始
+    pub base: NaiveDateTime,
+    pub zone: EventZone,
+    pub repeat: Option<Repeat>,
+    pub start: Option<DateTime<Utc>>,
+    pub lead_times: Vec<Duration>,
+    // 已和全局模板合并
+    pub format: FormatConfig,
+    pub mode: Mode,
+    pub milestones: Vec<Milestone>,
+}
+
+impl Event {
+    pub fn new(
+        countdown: &Countdown,
+        defaults: &Defaults,
+        format: &FormatConfig,
+    ) -> Result<Event, String> {
+        let title = &countdown.title;
+        let when = parse_when(&countdown.datetime, None).ok_or_else(|| {
+            i18n::tf(
+                "config.invalid_datetime",
+                &[
+                    ("title", title),
+                    ("datetime", &countdown.datetime),
+                    ("formats", &i18n::t("config.datetime_formats")),
+                ],
+            )
+        })?;
+        let offset = match when {
+            When::At(_, offset) => offset,
+            When::TimeOfDay(_) => None,
+        };
+        let zone = match (&countdown.timezone, offset) {
+            (Some(name), _) => EventZone::Named(name.parse::<Tz>().map_err(|_| {
+                i18n::tf(
+                    "config.invalid_timezone",
+                    &[("title", title), ("zone", name)],
+                )
+            })?),
+            (None, Some(offset)) => EventZone::Fixed(offset),
+            (None, None) => EventZone::Local,
+        };
+        // 带偏移的时间先换算到事件时区，再按该时区的日历重复
+        let (base, implied) = match when {
+            When::At(naive, Some(offset)) => {
+                let utc = naive - Duration::seconds(offset.local_minus_utc().into());
+                (zone.wall_time_of(utc.and_utc()), None)
+            }
+            When::At(naive, None) => (naive, None),
+            // 只写了钟点时按每天重复，总是取下一次
+            When::TimeOfDay(time) => (
+                zone.wall_time_of(Utc::now()).date().and_time(time),
+                Some(Repeat::Daily),
+            ),
+        };
+        let repeat = countdown
+            .repeat
+            .as_deref()
+            .map(|rule| {
+                rule.parse::<Repeat>().map_err(|err| {
+                    i18n::tf(
+                        "config.invalid_repeat",
+                        &[("title", title), ("error", &err)],
+                    )
+                })
+            })
+            .transpose()?;
+        let countup = countdown.mode == Mode::Countup;
+        if countup && repeat.is_some() {
+            return Err(i18n::tf("config.countup_repeat", &[("title", title)]));
+        }
+        if !countup && !countdown.milestones.is_empty() {
+            return Err(i18n::tf(
+                "config.milestones_need_countup",
+                &[("title", title)],
+            ));
+        }
+        let milestones = countdown
+            .milestones
+            .iter()
+            .map(|milestone| {
+                milestone.parse::<Milestone>().map_err(|err| {
+                    i18n::tf(
+                        "config.invalid_milestone",
+                        &[("title", title), ("error", &err)],
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        // 全局 [format] 在加载时单独检查，这里只查自己的，免得每条都报一遍
+        if let Some(own) = &countdown.format {
+            own.check(title)?;
+        }
+        // 正计时只写钟点时从今天这个时刻算起，不重复
+        let repeat = if countup { None } else { repeat.or(implied) };
+        let event = Event {
+            title: title.clone(),
+            base,
+            zone,
+            repeat,
+            start: countdown.resolve_start(zone)?,
+            lead_times: countdown.lead_times(defaults)?,
+            format: countdown
+                .format
+                .as_ref()
+                .map(|own| own.merged(format))
+                .unwrap_or_else(|| format.clone()),
+            mode: countdown.mode,
+            milestones,
+        };
+        if event.repeat.is_none() {
+            event.target(Utc::now())?;
+        }
+        Ok(event)
+    }
+
+    // 目标时间，重复事件取下一次发生的时间
+    pub fn target(&self, now: DateTime<Utc>) -> Result<Target, String> {
+        let zone = self.zone;
+        let (target, previous) = match &self.repeat {
+            Some(repeat) => {
+                // 保留一秒，让到点的那一秒仍能显示并触发通知
+                let after = zone.wall_time_of(now - Duration::seconds(1));
+                let previous = repeat.last_until(self.base, zone.wall_time_of(now));
+                (
+                    repeat.next_after(self.base, after).unwrap_or(self.base),
+                    previous,
+                )
+            }
+            None => (self.base, None),
+        };
+        let at = zone
+            .instant_of(target)
+            .ok_or_else(|| i18n::tf("config.missing_datetime", &[("title", &self.title)]))?;
+        Ok(Target {
+            at,
+            previous: previous.and_then(|previous| zone.instant_of(previous)),
+            start: self.start,
+            zone,
+        })
+    }
+}
+
+impl Countdown {
+    fn resolve_start(&self, zone: EventZone) -> Result<Option<DateTime<Utc>>, String> {
+        let Some(start) = self.start.as_deref() else {
+            return Ok(None);
+        };
+        let title = &self.title;
+        let Some(When::At(naive, offset)) = parse_when(start, None) else {
+            return Err(i18n::tf(
+                "config.invalid_start",
+                &[("title", title), ("start", &start)],
+            ));
+        };
+        let start = match offset {
+            Some(offset) => {
+                Some((naive - Duration::seconds(offset.local_minus_utc().into())).and_utc())
+            }
+            None => zone.instant_of(naive),
+        };
+        start
+            .map(Some)
+            .ok_or_else(|| i18n::tf("config.missing_start", &[("title", title)]))
+    }
+
+    // 提前提醒的时间，未单独配置时使用全局默认值
+    pub fn lead_times(&self, defaults: &Defaults) -> Result<Vec<Duration>, String> {
+        self.remind
+            .as_ref()
+            .unwrap_or(&defaults.remind)
+            .iter()
+            .map(|lead| {
+                parse_duration(lead).ok_or_else(|| {
+                    i18n::tf(
+                        "config.invalid_remind",
+                        &[("title", &self.title), ("remind", lead)],
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+// 解析 "1d"、"1h30m"、"10m" 这类时长
+pub fn parse_duration(input: &str) -> Option<Duration> {
+    let input = input.trim();
+    if input.is_empty() {
+        return None;
+    }
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for ch in input.chars() {
+        if ch.is_ascii_digit() {
+            number.push(ch);
+            continue;
+        }
+        let value: i64 = number.parse().ok()?;
+        number.clear();
+        // 数值过大时返回 None，不让 chrono 溢出 panic
+        let part = match ch {
+            'w' => Duration::try_weeks(value),
+            'd' => Duration::try_days(value),
+            'h' => Duration::try_hours(value),
+            'm' => Duration::try_minutes(value),
+            's' => Duration::try_seconds(value),
+            _ => return None,
+        }?;
+        total = total.checked_add(&part)?;
+    }
+    if !number.is_empty() {
+        return None;
+    }
+    Some(total)
+}
+
+pub fn describe_duration(duration: Duration) -> String {
+    let units = [
+        (duration.num_days(), "unit.day"),
+        (duration.num_hours() % 24, "unit.hour"),
+        (duration.num_minutes() % 60, "unit.minute"),
+        (duration.num_seconds() % 60, "unit.second"),
+    ];
+    let parts: Vec<String> = units
+        .iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| i18n::tfn(unit, *value, &[("count", value)]))
+        .collect();
+    if parts.is_empty() {
+        i18n::tfn("unit.second", 0, &[("count", &0)])
+    } else {
+        parts.join(i18n::t("unit.separator"))
+    }
+}
+
+// 日期时间的各种写法解析后的结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum When {
+    // 具体的时间，可带 UTC 偏移
+    At(NaiveDateTime, Option<FixedOffset>),
+    // 只写了钟点，取下一次到达的时间
+    TimeOfDay(NaiveTime),
+}
+
+const NAIVE_FORMATS: &[&str] = &[
+    DATETIME_FORMAT,
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+];
+
+// 所有日期时间写法都在这里解析；给出 now 时还接受 +2h30m、tomorrow 18:00、
+// next friday 18:00 这类相对写法（add 命令用），now 是事件时区的墙上时间
+pub fn parse_when(input: &str, now: Option<NaiveDateTime>) -> Option<When> {
+    let input = input.trim();
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(input)
+        .or_else(|_| DateTime::parse_from_str(input, &format!("{DATETIME_FORMAT} %z")))
+    {
+        return Some(When::At(datetime.naive_local(), Some(*datetime.offset())));
+    }
+    if let Some(naive) = NAIVE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(input, format).ok())
+    {
+        return Some(When::At(naive, None));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        return Some(When::At(date.and_time(NaiveTime::MIN), None));
+    }
+    if let Some(time) = parse_time(input) {
+        return Some(When::TimeOfDay(time));
+    }
+
+    let now = now?;
+    if let Some(duration) = input.strip_prefix('+').and_then(parse_duration) {
+        return Some(When::At(now.checked_add_signed(duration)?, None));
+    }
+    let input = input.to_lowercase();
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let (date, time) = match words.as_slice() {
+        ["today", time @ ..] => (now.date(), time),
+        ["tomorrow", time @ ..] => (now.date().succ_opt()?, time),
+        ["next", weekday, time @ ..] => {
+            let weekday: Weekday = weekday.parse().ok()?;
+            // 总是晚于今天，今天是周五时 next friday 指下周五
+            let days =
+                (weekday.num_days_from_monday() + 6 - now.weekday().num_days_from_monday()) % 7 + 1;
+            (now.date().checked_add_days(Days::new(days.into()))?, time)
+        }
+        _ => return None,
+    };
+    let time = match time {
+        [] => NaiveTime::MIN,
+        [time] => parse_time(time)?,
+        _ => return None,
+    };
+    Some(When::At(date.and_time(time), None))
+}
+
+fn parse_time(input: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(input, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(input, "%H:%M"))
+        .ok()
+}
+
+// add 命令用：把各种写法换算成配置文件里的标准格式，相对写法按事件时区的当前时间计算
+pub fn normalize_datetime(
+    input: &str,
+    timezone: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<String, String> {
+    // 时区无效时先按本地时间换算，后面的检查会报告时区错误
+    let zone = timezone
+        .and_then(|name| name.parse::<Tz>().ok())
+        .map_or(EventZone::Local, EventZone::Named);
+    let now = zone.wall_time_of(now);
+    let invalid = || {
+        i18n::tf(
+            "config.invalid_when",
+            &[
+                ("datetime", &input),
+                ("formats", &i18n::t("config.datetime_formats")),
+            ],
+        )
+    };
+    Ok(match parse_when(input, Some(now)).ok_or_else(invalid)? {
+        When::At(naive, None) => naive.format(DATETIME_FORMAT).to_string(),
+        When::At(naive, Some(offset)) => format!("{} {offset}", naive.format(DATETIME_FORMAT)),
+        When::TimeOfDay(time) => {
+            let today = now.date().and_time(time);
+            let next = if today > now {
+                today
+            } else {
+                today.checked_add_days(Days::new(1)).ok_or_else(invalid)?
+            };
+            next.format(DATETIME_FORMAT).to_string()
+        }
+    })
+}
+
+// 倒计时行的模板，按剩余时间分为四档：一天以上、一天以内、到点、已过去
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FormatConfig {
+    #[serde(default)]
+    pub far: Option<String>,
+    #[serde(default)]
+    pub day: Option<String>,
+    #[serde(default)]
+    pub now: Option<String>,
+    #[serde(default)]
+    pub past: Option<String>,
+    // 正计时开始之后
+    #[serde(default)]
+    pub up: Option<String>,
+    // 未来的事件默认显示时钟，relative 显示 "3 weeks 2 days" 这样的相对时间
+    #[serde(default)]
+    pub style: Option<CountdownStyle>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CountdownStyle {
+    Clock,
+    Relative,
+}
+
+impl FormatConfig {
+    // 单个倒计时的模板优先，未设置的档位使用全局模板
+    pub fn merged(&self, global: &FormatConfig) -> FormatConfig {
+        FormatConfig {
+            far: self.far.clone().or_else(|| global.far.clone()),
+            day: self.day.clone().or_else(|| global.day.clone()),
+            now: self.now.clone().or_else(|| global.now.clone()),
+            past: self.past.clone().or_else(|| global.past.clone()),
+            up: self.up.clone().or_else(|| global.up.clone()),
+            style: self.style.or(global.style),
+        }
+    }
+
+    pub fn check(&self, title: &str) -> Result<(), String> {
+        let unknown: Vec<String> = [&self.far, &self.day, &self.now, &self.past, &self.up]
+            .into_iter()
+            .flatten()
+            .flat_map(|template| template::unknown_fields(template))
+            .collect();
+        if unknown.is_empty() {
+            return Ok(());
+        }
+        Err(i18n::tf(
+            "config.invalid_format",
+            &[("title", &title), ("fields", &unknown.join(", "))],
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PomodoroConfig {
+    // 阶段结束后自动进入下一阶段
+    #[serde(default)]
+    pub auto_advance: bool,
+    // 自动模式下，休息结束后等待 start 再开始工作
+    #[serde(default)]
+    pub confirm_work: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotifyConfig {
+    #[serde(default = "default_sinks")]
+    pub sinks: Vec<String>,
+    #[serde(default)]
+    pub sound: Option<String>,
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub webhook: Option<String>,
+    #[serde(default)]
+    pub log_file: Option<String>,
+}
+
+fn default_sinks() -> Vec<String> {
+    vec!["desktop".to_string()]
+}
+
+impl NotifyConfig {
+    // 拼错或缺少设置的通道会让提醒悄悄失效，加载时就指出来
+    pub fn check(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        for sink in &self.sinks {
+            let setting = match sink.as_str() {
+                // sound 可以由命令行 -s 提供，发送时再检查
+                "desktop" | "bell" | "sound" => None,
+                "command" => self.command.is_none().then_some("command"),
+                "webhook" => self.webhook.is_none().then_some("webhook"),
+                "log" => self.log_file.is_none().then_some("log_file"),
+                _ => {
+                    errors.push(i18n::tf("config.unknown_sink", &[("sink", sink)]));
+                    continue;
+                }
+            };
+            if let Some(setting) = setting {
+                errors.push(i18n::tf(
+                    "config.missing_sink_setting",
+                    &[("sink", sink), ("setting", &setting)],
+                ));
+            }
+        }
+        errors
+    }
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        NotifyConfig {
+            sinks: default_sinks(),
+            sound: None,
+            command: None,
+            webhook: None,
+            log_file: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Defaults {
+    #[serde(default)]
+    pub remind: Vec<String>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct CountDownData {
+    #[serde(default)]
+    pub lang: Option<Lang>,
+    #[serde(default)]
+    pub defaults: Defaults,
+    #[serde(default)]
+    pub notify: NotifyConfig,
+    #[serde(default)]
+    pub pomodoro: PomodoroConfig,
+    #[serde(default)]
+    pub format: FormatConfig,
     pub countdown: Vec<Countdown>,
+    // 启用的倒计时，加载时解析
+    #[serde(skip)]
+    pub events: Vec<Event>,
+}
+
+impl CountDownData {
+    // 只在 TOML 本身无法解析时失败，跳过有问题的倒计时，错误留给 validate 报告
+    pub fn load_valid(file: &str, contents: &str) -> Result<CountDownData, ConfigError> {
+        let mut data: CountDownData = toml::from_str(contents)
+            .map_err(|err| ConfigError::Parse(parse_error(file, contents, &err)))?;
+        data.events = data
+            .countdown
+            .iter()
+            .filter(|countdown| countdown.enabled)
+            .filter_map(|countdown| Event::new(countdown, &data.defaults, &data.format).ok())
+            .collect();
+        Ok(data)
+    }
+
+    // 解析配置并检查通知、显示模板和每个启用的倒计时，全部有效才返回
+    pub fn load(file: &str, contents: &str) -> Result<CountDownData, ConfigError> {
+        let mut data: CountDownData = toml::from_str(contents)
+            .map_err(|err| ConfigError::Parse(parse_error(file, contents, &err)))?;
+        let mut settings = data.notify.check();
+        settings.extend(data.format.check("[format]").err());
+        let mut entries = Vec::new();
+        for (index, countdown) in data.countdown.iter().enumerate() {
+            if !countdown.enabled {
+                continue;
+            }
+            match Event::new(countdown, &data.defaults, &data.format) {
+                Ok(event) => data.events.push(event),
+                Err(message) => entries.push(EntryError {
+                    index,
+                    title: countdown.title.clone(),
+                    message,
+                }),
+            }
+        }
+        if settings.is_empty() && entries.is_empty() {
+            Ok(data)
+        } else {
+            Err(ConfigError::Invalid { settings, entries })
+        }
+    }
+}
+
+// 只读取 lang，配置其它部分有错误时也能先确定界面语言
+pub fn configured_lang(config_file: &str) -> Option<Lang> {
+    #[derive(Deserialize)]
+    struct LangOnly {
+        lang: Option<Lang>,
+    }
+    let contents = std::fs::read_to_string(config_file).ok()?;
+    toml::from_str::<LangOnly>(&contents).ok()?.lang
 }
 
 #[derive(Debug, Clone)]
 pub struct CountDownConfig {
     pub data: Arc<Mutex<CountDownData>>,
     config_filename: String,
+    // 每次配置更新时加一，调度器据此提前醒来
+    revision: Arc<watch::Sender<u64>>,
+    // 上次加载的文件内容，没变化时不更新
+    contents: String,
+    // 最近一次重新加载失败的原因，修好之前一直显示
+    reload_error: Arc<Mutex<Option<ConfigError>>>,
 }
 
-impl CountDownConfig {
-    pub fn try_new(config_filename: String) -> Result<Self, Box<dyn std::error::Error>> {
-        let mut file = match std::fs::File::open(config_filename.clone()) {
-            Ok(file) => file,
-            Err(err) => {
-                println!("Error: Cannot open file '{config_filename}'");
-                return Err(err.into());
-            }
-        };
+fn read_config(file: &str) -> Result<String, ConfigError> {
+    std::fs::read_to_string(file).map_err(|err| {
+        ConfigError::Io(i18n::tf(
+            "error.open_file",
+            &[("file", &file), ("error", &err)],
+        ))
+    })
+}
 
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
-        let countdown_data: CountDownData = toml::from_str(&contents)?;
+impl CountDownConfig {
+    pub fn try_new(config_filename: String) -> Result<Self, ConfigError> {
+        let contents = read_config(&config_filename)?;
+        let countdown_data = CountDownData::load(&config_filename, &contents)?;
         Ok(Self {
             data: Arc::new(Mutex::new(countdown_data)),
             config_filename,
+            revision: Arc::new(watch::Sender::new(0)),
+            contents,
+            reload_error: Arc::new(Mutex::new(None)),
         })
     }
 
+    pub fn file_name(&self) -> &str {
+        &self.config_filename
+    }
+
+    pub async fn reload_error(&self) -> Option<ConfigError> {
+        self.reload_error.lock().await.clone()
+    }
+
+    async fn load_file(&mut self) -> Result<(), ConfigError> {
+        let contents = read_config(&self.config_filename)?;
+        if contents == self.contents {
+            return Ok(());
+        }
+        let countdown_data = CountDownData::load(&self.config_filename, &contents)?;
+        self.set_config(countdown_data).await;
+        self.contents = contents;
+        Ok(())
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.revision.subscribe()
+    }
+
     pub async fn set_config(&mut self, data: CountDownData) {
         let mut data_config = self.data.lock().await;
+        data_config.lang = data.lang;
+        data_config.defaults = data.defaults;
+        data_config.notify = data.notify;
+        data_config.pomodoro = data.pomodoro;
+        data_config.format = data.format;
         data_config.countdown = data.countdown;
+        data_config.events = data.events;
+        self.revision.send_modify(|revision| *revision += 1);
     }
 
     pub async fn get_config(&self) -> CountDownData {
         let data_config = self.data.lock().await;
         CountDownData {
+            lang: data_config.lang,
+            defaults: data_config.defaults.clone(),
+            notify: data_config.notify.clone(),
+            pomodoro: data_config.pomodoro.clone(),
+            format: data_config.format.clone(),
             countdown: data_config.countdown.clone(),
+            events: data_config.events.clone(),
         }
     }
 }
 
+// 字节偏移所在的行号和列号，都从 1 开始
+pub fn line_column(contents: &str, offset: usize) -> (usize, usize) {
+    let before = &contents[..offset.min(contents.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .count()
+        + 1;
+    (line, column)
+}
+
+// TOML 解析错误附上文件名、行号和列号，只保留第一行说明
+fn parse_error(file: &str, contents: &str, err: &toml::de::Error) -> String {
+    let location = match err.span() {
+        Some(span) => {
+            let (line, column) = line_column(contents, span.start);
+            format!("{file}:{line}:{column}")
+        }
+        None => file.to_string(),
+    };
+    let message = err.message().lines().next().unwrap_or_default();
+    i18n::tf(
+        "config.parse_error",
+        &[("location", &location), ("message", &message)],
+    )
+}
+
 impl HotReload for CountDownConfig {
-    async fn reload(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let mut file = std::fs::File::open(self.config_filename.clone())?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
-        let countdown_data = toml::from_str(&contents)?;
-        self.set_config(countdown_data).await;
-        Ok(())
+    // 失败时保留上次有效的配置，并记下原因
+    async fn reload(&mut self) -> Result<(), ConfigError> {
+        let result = self.load_file().await;
+        let error = result.as_ref().err().cloned();
+        let mut reload_error = self.reload_error.lock().await;
+        if *reload_error != error {
+            *reload_error = error;
+            self.revision.send_modify(|revision| *revision += 1);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(input: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(input, DATETIME_FORMAT).unwrap()
+    }
+
+    fn utc(input: &str) -> DateTime<Utc> {
+        at(input).and_utc()
+    }
+
+    #[test]
+    fn parse_when_accepts_absolute_forms() {
+        let expected = Some(When::At(at("2025-07-12 18:00:00"), None));
+        for input in [
+            "2025-07-12 18:00:00",
+            "2025-07-12 18:00",
+            "2025-07-12T18:00:00",
+            " 2025-07-12T18:00 ",
+        ] {
+            assert_eq!(parse_when(input, None), expected, "{input}");
+        }
+        assert_eq!(
+            parse_when("2025-07-12", None),
+            Some(When::At(at("2025-07-12 00:00:00"), None))
+        );
+        let offset = FixedOffset::east_opt(8 * 3600);
+        for input in ["2025-07-12T18:00:00+08:00", "2025-07-12 18:00:00 +0800"] {
+            assert_eq!(
+                parse_when(input, None),
+                Some(When::At(at("2025-07-12 18:00:00"), offset)),
+                "{input}"
+            );
+        }
+        assert_eq!(
+            parse_when("18:30", None),
+            Some(When::TimeOfDay(NaiveTime::from_hms_opt(18, 30, 0).unwrap()))
+        );
+        assert_eq!(parse_when("2025-02-30", None), None);
+    }
+
+    #[test]
+    fn parse_when_relative_forms_need_now() {
+        assert_eq!(parse_when("+2h30m", None), None);
+        assert_eq!(parse_when("tomorrow 18:00", None), None);
+
+        // 2025-07-11 是周五
+        let now = Some(at("2025-07-11 09:15:00"));
+        let when = |input| parse_when(input, now);
+        assert_eq!(
+            when("+2h30m"),
+            Some(When::At(at("2025-07-11 11:45:00"), None))
+        );
+        assert_eq!(
+            when("today"),
+            Some(When::At(at("2025-07-11 00:00:00"), None))
+        );
+        assert_eq!(
+            when("Tomorrow 18:00"),
+            Some(When::At(at("2025-07-12 18:00:00"), None))
+        );
+        assert_eq!(
+            when("next friday 08:00"),
+            Some(When::At(at("2025-07-18 08:00:00"), None))
+        );
+        assert_eq!(
+            when("next mon"),
+            Some(When::At(at("2025-07-14 00:00:00"), None))
+        );
+        assert_eq!(when("next someday"), None);
+    }
+
+    #[test]
+    fn parse_when_rejects_out_of_range_offsets() {
+        let now = Some(at("2025-07-11 09:15:00"));
+        assert_eq!(parse_when("+99999999d", now), None);
+        assert_eq!(parse_when("+999999999999d", now), None);
+        assert_eq!(parse_duration("999999999999d"), None);
+        assert_eq!(
+            parse_duration("1w2d3h4m5s").map(|d| d.num_seconds()),
+            Some(788_645)
+        );
+    }
+
+    #[test]
+    fn normalize_datetime_in_event_zone() {
+        let now = utc("2025-07-11 19:00:00");
+        let normalize = |input| normalize_datetime(input, Some("UTC"), now);
+        assert_eq!(normalize("18:00").as_deref(), Ok("2025-07-12 18:00:00"));
+        assert_eq!(normalize("20:00").as_deref(), Ok("2025-07-11 20:00:00"));
+        assert_eq!(normalize("+1d").as_deref(), Ok("2025-07-12 19:00:00"));
+        assert_eq!(
+            normalize("2025-07-12T18:00:00+08:00").as_deref(),
+            Ok("2025-07-12 18:00:00 +08:00")
+        );
+        assert_eq!(
+            normalize_datetime("tomorrow 09:00", Some("Asia/Shanghai"), now).as_deref(),
+            Ok("2025-07-13 09:00:00")
+        );
+        assert!(normalize("+99999999d").is_err());
+        assert!(normalize("soon").is_err());
     }
 }
@@ -0,0 +1,140 @@
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::Mutex;
+
+use crate::config::CountDownConfig;
+use crate::i18n;
+use crate::output;
+use crate::pomodoro::{PomodoroState, PomodoroTimer};
+use crate::scheduler::{self, Scheduler};
+use crate::CliArgs;
+
+pub fn socket_path(custom: Option<&str>) -> PathBuf {
+    match custom {
+        Some(path) => PathBuf::from(path),
+        None => dirs::runtime_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("countdown_cli.sock"),
+    }
+}
+
+pub async fn run_daemon(
+    if_running: Arc<AtomicBool>,
+    config: CountDownConfig,
+    notify_sound: Option<String>,
+    cli_args: Option<&CliArgs>,
+    socket: PathBuf,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Ok(metadata) = std::fs::symlink_metadata(&socket) {
+        if UnixStream::connect(&socket).await.is_ok() {
+            return Err(i18n::tf("daemon.running", &[("socket", &socket.display())]).into());
+        }
+        // 只删除上次异常退出留下的 socket 文件，别的文件原样保留
+        if !metadata.file_type().is_socket() {
+            return Err(i18n::tf("daemon.not_socket", &[("socket", &socket.display())]).into());
+        }
+        std::fs::remove_file(&socket)?;
+    }
+    let listener = UnixListener::bind(&socket)?;
+    println!(
+        "{}",
+        i18n::tf("daemon.listening", &[("socket", &socket.display())])
+    );
+
+    let pomodoro = Arc::new(Mutex::new(PomodoroTimer::load(cli_args)));
+
+    let pomodoro_for_accept = pomodoro.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(handle_client(stream, pomodoro_for_accept.clone()));
+        }
+    });
+
+    let mut scheduler = Scheduler::new(config, notify_sound, cli_args.and_then(|args| args.lang));
+    let mut reported: Vec<String> = Vec::new();
+    while if_running.load(Ordering::SeqCst) {
+        let now = Utc::now();
+        scheduler.update(now).await;
+        // 没有界面，配置错误变化时打印到标准错误
+        if scheduler.errors != reported {
+            if !scheduler.errors.is_empty() {
+                eprintln!("{}", i18n::t("ui.pane.diagnostics"));
+            }
+            for err in &scheduler.errors {
+                eprintln!("{err}");
+            }
+            reported = scheduler.errors.clone();
+        }
+
+        let mut pomodoro_lock = pomodoro.lock().await;
+        pomodoro_lock.set_cycle(cli_args, &scheduler.pomodoro);
+        if pomodoro_lock.state != PomodoroState::Idle
+            && pomodoro_lock.remaining_time() == Some(Duration::from_secs(0))
+        {
+            pomodoro_lock.next_state();
+            scheduler
+                .notifiers
+                .send(i18n::t("pomodoro.phase_end_notify"), "");
+        }
+        // 没有界面，只需在阶段结束时醒来
+        let phase_end = pomodoro_lock
+            .remaining_time()
+            .filter(|_| pomodoro_lock.state != PomodoroState::Idle && !pomodoro_lock.is_paused())
+            .unwrap_or(scheduler::MAX_SLEEP);
+        drop(pomodoro_lock);
+
+        let wait = scheduler.wait_time(now, phase_end);
+        scheduler.sleep(wait).await;
+    }
+
+    pomodoro.lock().await.save();
+    let _ = std::fs::remove_file(&socket);
+    Ok(())
+}
+
+async fn handle_client(stream: UnixStream, pomodoro: Arc<Mutex<PomodoroTimer>>) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let reply = match line.trim() {
+            "" => continue,
+            "status" => serde_json::to_string(&output::pomodoro_status(&*pomodoro.lock().await))
+                .unwrap_or_default(),
+            command => match pomodoro.lock().await.apply_command(command) {
+                Ok(()) => "ok".to_string(),
+                Err(err) => format!("error: {err}"),
+            },
+        };
+        if writer
+            .write_all(format!("{reply}\n").as_bytes())
+            .await
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+pub async fn send_command(
+    socket: &Path,
+    command: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let stream = UnixStream::connect(socket).await.map_err(|err| {
+        i18n::tf(
+            "daemon.connect",
+            &[("socket", &socket.display()), ("error", &err)],
+        )
+    })?;
+    let (reader, mut writer) = stream.into_split();
+    writer.write_all(format!("{command}\n").as_bytes()).await?;
+    let mut reply = String::new();
+    BufReader::new(reader).read_line(&mut reply).await?;
+    Ok(reply.trim_end().to_string())
+}
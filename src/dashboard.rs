@@ -0,0 +1,563 @@
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local, Utc};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Color;
+use unicode_width::UnicodeWidthStr;
+
+use crate::bigdigits::{self, BigTarget};
+use crate::config::{CountdownStyle, Mode};
+use crate::engine::Scheduled;
+use crate::i18n;
+use crate::pomodoro::{PomodoroState, PomodoroTimer};
+use crate::scheduler;
+use crate::template::{self, CountdownValues, Piece};
+use crate::tui::{line_width, Buffer, Line, Span, Style};
+
+const NOTICE_TIMEOUT: Duration = Duration::from_secs(5);
+// 显示毫秒时的刷新间隔
+const FRAME_INTERVAL: Duration = Duration::from_millis(50);
+const POMODORO_PANE_HEIGHT: u16 = 5;
+const MIN_WIDTH: u16 = 20;
+const MIN_HEIGHT: u16 = POMODORO_PANE_HEIGHT + 5;
+// 倒计时列表至少保留的高度（含边框）
+const MIN_LIST_HEIGHT: u16 = 3;
+// 诊断面板最多显示的错误行数
+const MAX_DIAGNOSTIC_ROWS: usize = 4;
+const COUNTDOWN_BAR_WIDTH: usize = 10;
+const PARTIAL_BLOCKS: [&str; 8] = [" ", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+
+const HELP: &[&str] = &[
+    "help.title",
+    "help.start",
+    "help.start_task",
+    "help.short",
+    "help.long",
+    "help.stop",
+    "help.next",
+    "help.pause",
+    "help.resume",
+    "help.work",
+    "help.short_minutes",
+    "help.long_minutes",
+    "help.interval",
+    "help.big",
+    "help.help",
+    "help.quit",
+];
+
+pub enum Action {
+    None,
+    Quit,
+    Command(String),
+}
+
+// 界面状态：命令输入、状态栏消息、帮助面板
+#[derive(Default)]
+pub struct Dashboard {
+    input: String,
+    notice: Option<(String, bool, Instant)>,
+    show_help: bool,
+    big: Option<BigTarget>,
+    // 上一帧是否显示了毫秒，决定刷新频率
+    precise: bool,
+}
+
+impl Dashboard {
+    pub fn new(big: Option<BigTarget>) -> Self {
+        Dashboard {
+            big,
+            ..Dashboard::default()
+        }
+    }
+
+    pub fn notice(&mut self, message: impl Into<String>) {
+        self.notice = Some((message.into(), false, Instant::now()));
+    }
+
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.notice = Some((message.into(), true, Instant::now()));
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Action {
+        if key.kind == KeyEventKind::Release {
+            return Action::None;
+        }
+        match key.code {
+            KeyCode::Char('c') | KeyCode::Char('d')
+                if key.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                Action::Quit
+            }
+            KeyCode::Char(symbol) => {
+                self.input.push(symbol);
+                Action::None
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+                Action::None
+            }
+            KeyCode::Esc => {
+                self.input.clear();
+                self.show_help = false;
+                Action::None
+            }
+            KeyCode::Enter => {
+                let input = std::mem::take(&mut self.input);
+                match input.split_whitespace().collect::<Vec<_>>().as_slice() {
+                    [] => self.show_help = false,
+                    ["help"] => self.show_help = !self.show_help,
+                    ["quit"] | ["exit"] => return Action::Quit,
+                    ["big"] => {
+                        self.big = match self.big {
+                            None => Some(BigTarget::Pomodoro),
+                            Some(BigTarget::Pomodoro) => Some(BigTarget::Countdown),
+                            Some(BigTarget::Countdown) => None,
+                        }
+                    }
+                    ["big", "off"] => self.big = None,
+                    ["big", target] => match clap::ValueEnum::from_str(target, true) {
+                        Ok(target) => self.big = Some(target),
+                        Err(_) => {
+                            self.error(i18n::tf("ui.unknown_big_target", &[("target", target)]))
+                        }
+                    },
+                    _ => return Action::Command(input.trim().to_string()),
+                }
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    // 画出一帧，同时返回输入光标的位置
+    pub fn render(
+        &mut self,
+        width: u16,
+        height: u16,
+        pomodoro: &PomodoroTimer,
+        scheduled: &[Scheduled],
+        errors: &[String],
+        now: DateTime<Utc>,
+    ) -> (Buffer, Option<(u16, u16)>) {
+        let mut buffer = Buffer::new(width, height);
+        self.precise = false;
+        if width < MIN_WIDTH || height < MIN_HEIGHT {
+            buffer.put_str(0, 0, i18n::t("ui.too_small"), Style::fg(Color::Red), width);
+            return (buffer, None);
+        }
+
+        buffer.draw_box(
+            0,
+            0,
+            width,
+            POMODORO_PANE_HEIGHT,
+            i18n::t("ui.pane.pomodoro"),
+        );
+        let bar_width = (width as usize).saturating_sub(4 + 6);
+        for (row, line) in pomodoro_lines(pomodoro, bar_width).iter().enumerate() {
+            buffer.put_line(2, 1 + row as u16, line, width - 2);
+        }
+
+        // 配置错误放在列表下方的诊断面板里，放不下时退回到状态栏
+        let diagnostics_height = match errors.len() {
+            0 => 0,
+            count => {
+                let room = height - POMODORO_PANE_HEIGHT - 2 - MIN_LIST_HEIGHT;
+                if room >= 3 {
+                    (count.min(MAX_DIAGNOSTIC_ROWS) as u16 + 2).min(room)
+                } else {
+                    0
+                }
+            }
+        };
+
+        let mut list_top = POMODORO_PANE_HEIGHT;
+        if let Some(target) = self.big {
+            let room = height - list_top - 2 - MIN_LIST_HEIGHT - diagnostics_height;
+            let (title, text, color) = big_text(target, pomodoro, scheduled, now);
+            self.precise |= text.contains('.');
+            if let Some((horizontal, vertical)) =
+                bigdigits::fit(&text, (width - 4) as usize, room.saturating_sub(2) as usize)
+            {
+                let pane_height = bigdigits::rendered_height(vertical) as u16 + 2;
+                buffer.draw_box(0, list_top, width, pane_height, &title);
+                for (row, line) in bigdigits::render(&text, horizontal, vertical)
+                    .iter()
+                    .enumerate()
+                {
+                    let x = (width - line.width() as u16) / 2;
+                    buffer.put_str(
+                        x,
+                        list_top + 1 + row as u16,
+                        line,
+                        Style::fg(color),
+                        width - 2,
+                    );
+                }
+                list_top += pane_height;
+            }
+        }
+        let list_height = height - list_top - 2 - diagnostics_height;
+        let rows = (list_height - 2) as usize;
+        let mut precise = Vec::new();
+        let lines: Vec<Line> = if self.show_help {
+            HELP.iter()
+                .map(|key| vec![Span::raw(i18n::t(key))])
+                .collect()
+        } else {
+            // 有任何倒计时带进度时，其余行留出同样的宽度以便对齐
+            let with_progress = scheduled
+                .iter()
+                .any(|entry| entry.target.progress(now).is_some());
+            scheduled
+                .iter()
+                .map(|entry| {
+                    let mut line = Vec::new();
+                    match entry.target.progress(now) {
+                        Some(ratio) => line.extend(progress_bar(ratio, COUNTDOWN_BAR_WIDTH)),
+                        None if with_progress => {
+                            line.push(Span::raw(" ".repeat(COUNTDOWN_BAR_WIDTH + 6)))
+                        }
+                        None => {}
+                    }
+                    if with_progress {
+                        line.push(Span::raw(" "));
+                    }
+                    let (text, with_ms) = countdown_line(entry, now);
+                    line.extend(text);
+                    precise.push(with_ms);
+                    line
+                })
+                .collect()
+        };
+        buffer.draw_box(
+            0,
+            list_top,
+            width,
+            list_height,
+            i18n::t(if self.show_help {
+                "ui.pane.help"
+            } else {
+                "ui.pane.countdown"
+            }),
+        );
+        // 只看实际显示出来的行
+        self.precise |= precise.iter().take(rows).any(|with_ms| *with_ms);
+        fill_pane(&mut buffer, list_top, list_height, width, &lines);
+
+        let status_row = height - 2;
+        if diagnostics_height > 0 {
+            let top = status_row - diagnostics_height;
+            buffer.draw_box(
+                0,
+                top,
+                width,
+                diagnostics_height,
+                i18n::t("ui.pane.diagnostics"),
+            );
+            let lines: Vec<Line> = errors
+                .iter()
+                .map(|error| vec![Span::styled(error.as_str(), Style::fg(Color::Red))])
+                .collect();
+            fill_pane(&mut buffer, top, diagnostics_height, width, &lines);
+        }
+        let status_style = Style::default().reverse();
+        buffer.fill_row(status_row, status_style);
+        let (message, is_error) = match &self.notice {
+            Some((message, is_error, at)) if at.elapsed() < NOTICE_TIMEOUT => {
+                (message.clone(), *is_error)
+            }
+            _ if diagnostics_height > 0 => (i18n::t("ui.hint").to_string(), false),
+            _ => match errors {
+                [] => (i18n::t("ui.hint").to_string(), false),
+                [error] => (error.clone(), true),
+                [error, rest @ ..] => (format!("{error} (+{})", rest.len()), true),
+            },
+        };
+        let message_style = if is_error {
+            Style::fg(Color::Red).reverse()
+        } else {
+            status_style
+        };
+        buffer.put_str(1, status_row, &message, message_style, width);
+
+        let input_row = height - 1;
+        let end = buffer.put_str(0, input_row, "> ", Style::default().bold(), width);
+        // 输入过长时只显示末尾部分
+        let room = (width - end - 1) as usize;
+        let mut visible = self.input.as_str();
+        while visible.width() > room {
+            let mut chars = visible.chars();
+            chars.next();
+            visible = chars.as_str();
+        }
+        let cursor_x = buffer.put_str(end, input_row, visible, Style::default(), width);
+        (buffer, Some((cursor_x, input_row)))
+    }
+
+    // 距离画面下一次变化的时间：显示毫秒时按帧刷新，否则等到下一个整秒
+    pub fn next_frame(&self, pomodoro: &PomodoroTimer, now: DateTime<Utc>) -> Duration {
+        if self.precise {
+            return FRAME_INTERVAL;
+        }
+        let wall = scheduler::until_next_second(now);
+        // 番茄钟的整秒和墙上时间的整秒不对齐，分别计算
+        match pomodoro.remaining_time() {
+            Some(remaining) if pomodoro.state != PomodoroState::Idle && !pomodoro.is_paused() => {
+                match remaining.subsec_nanos() {
+                    0 => wall,
+                    nanos => wall.min(Duration::from_nanos(nanos as u64)),
+                }
+            }
+            _ => wall,
+        }
+    }
+}
+
+// 把若干行写进带边框的面板，放不下的行用 … 标记，多出的行数标在下边框上
+fn fill_pane(buffer: &mut Buffer, top: u16, pane_height: u16, width: u16, lines: &[Line]) {
+    let rows = (pane_height - 2) as usize;
+    for (row, line) in lines.iter().take(rows).enumerate() {
+        let y = top + 1 + row as u16;
+        let end = buffer.put_line(2, y, line, width - 2);
+        if line_width(line) > (end - 2) as usize {
+            buffer.put_str(width - 3, y, "…", Style::default(), width - 2);
+        }
+    }
+    if lines.len() > rows {
+        let more = format!(" +{} ", lines.len() - rows);
+        let x = width.saturating_sub(more.width() as u16 + 2);
+        buffer.put_str(x, top + pane_height - 1, &more, Style::default(), width - 1);
+    }
+}
+
+// 大号数字显示的标题、文字和颜色
+fn big_text(
+    target: BigTarget,
+    pomodoro: &PomodoroTimer,
+    scheduled: &[Scheduled],
+    now: DateTime<Utc>,
+) -> (String, String, Color) {
+    match target {
+        BigTarget::Pomodoro => match (pomodoro.state, pomodoro.remaining_time()) {
+            (PomodoroState::Idle, _) | (_, None) => (
+                i18n::t("ui.pane.pomodoro").to_string(),
+                "--:--".to_string(),
+                Color::DarkGrey,
+            ),
+            (state, Some(remaining)) => (
+                state.label().to_string(),
+                format!(
+                    "{:02}:{:02}",
+                    remaining.as_secs() / 60,
+                    remaining.as_secs() % 60
+                ),
+                if pomodoro.is_paused() {
+                    Color::DarkGrey
+                } else {
+                    Color::Cyan
+                },
+            ),
+        },
+        BigTarget::Countdown => match scheduled.iter().find(|entry| entry.target.at > now) {
+            None => (
+                i18n::t("ui.pane.countdown").to_string(),
+                "--:--:--".to_string(),
+                Color::DarkGrey,
+            ),
+            Some(entry) => {
+                let remaining = entry.target.at - now;
+                let clock = format!(
+                    "{:02}:{:02}:{:02}",
+                    remaining.num_hours() % 24,
+                    remaining.num_minutes() % 60,
+                    remaining.num_seconds() % 60
+                );
+                let text = match remaining.num_days() {
+                    0 => format!("{clock}.{}", remaining.num_milliseconds() % 1000 / 100),
+                    days => format!("{days}d {clock}"),
+                };
+                (entry.title.clone(), text, Color::Yellow)
+            }
+        },
+    }
+}
+
+// 用八分之一方块画出平滑的进度条，后跟百分比，总宽 width + 6
+fn progress_bar(ratio: f64, width: usize) -> Line {
+    let eighths = (ratio.clamp(0.0, 1.0) * (width * 8) as f64).round() as usize;
+    let full = eighths / 8;
+    let mut bar = "█".repeat(full);
+    if full < width {
+        bar.push_str(PARTIAL_BLOCKS[eighths % 8]);
+        bar.push_str(&" ".repeat(width - full - 1));
+    }
+    vec![
+        Span::styled("▕", Style::fg(Color::DarkGrey)),
+        Span::styled(bar, Style::fg(Color::Green)),
+        Span::styled("▏", Style::fg(Color::DarkGrey)),
+        Span::raw(format!("{:>3.0}%", ratio * 100.0)),
+    ]
+}
+
+// 按模板拼出一行，{name} 处换成带样式的参数，未知的变量原样保留
+fn template_line(template: &str, field: impl Fn(&str, Option<&str>) -> Option<Span>) -> Line {
+    template::parse(template)
+        .into_iter()
+        .map(|piece| match piece {
+            Piece::Text(text) => Span::raw(text),
+            Piece::Field(name, spec) => field(name, spec).unwrap_or_else(|| {
+                Span::raw(match spec {
+                    Some(spec) => format!("{{{name}:{spec}}}"),
+                    None => format!("{{{name}}}"),
+                })
+            }),
+        })
+        .collect()
+}
+
+fn clock(secs: u64) -> String {
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+fn pomodoro_lines(pomodoro: &PomodoroTimer, bar_width: usize) -> Vec<Line> {
+    let mut lines = Vec::new();
+    match (pomodoro.state, pomodoro.remaining_time()) {
+        (PomodoroState::Idle, _) | (_, None) => {
+            let text = match pomodoro.time_since_last_completion() {
+                Some(elapsed) => {
+                    i18n::tf("ui.idle_since", &[("elapsed", &clock(elapsed.as_secs()))])
+                }
+                None => i18n::t("pomodoro.not_started").to_string(),
+            };
+            lines.push(vec![Span::raw(text)]);
+        }
+        (state, Some(remaining)) => {
+            let mut line = template_line(i18n::t("ui.state"), |name, _| match name {
+                "phase" => Some(Span::styled(state.label(), Style::fg(Color::Cyan).bold())),
+                "remaining" => Some(Span::styled(
+                    clock(remaining.as_secs()),
+                    Style::fg(Color::Yellow),
+                )),
+                _ => None,
+            });
+            if pomodoro.is_paused() {
+                line.push(Span::styled(i18n::t("ui.paused"), Style::fg(Color::Red)));
+            }
+            lines.push(line);
+            if let Some(ratio) = pomodoro.progress() {
+                lines.push(progress_bar(ratio, bar_width));
+            }
+        }
+    }
+    let mut line = vec![Span::raw(i18n::tf(
+        "ui.completed",
+        &[("count", &pomodoro.completed_work_sessions)],
+    ))];
+    if let Some(task) = pomodoro.task.as_deref() {
+        line.push(Span::raw(i18n::tf("ui.task", &[("task", &task)])));
+    }
+    lines.push(line);
+    lines
+}
+
+// 返回这一行以及是否显示了毫秒
+fn countdown_line(entry: &Scheduled, now: DateTime<Utc>) -> (Line, bool) {
+    let Scheduled {
+        title,
+        target,
+        format,
+        ..
+    } = entry;
+    let remaining = target.at - now;
+    let number = Style::fg(Color::Yellow);
+    let relative = format.style == Some(CountdownStyle::Relative);
+
+    // 配置了模板的档位用配置，否则用语言包里的默认模板
+    let counting_up = entry.mode == Mode::Countup && remaining <= chrono::Duration::zero();
+    let (custom, default, title_style, number_style) = match remaining.num_seconds() {
+        // 正计时开始之后显示已经过去的时间，开始之前和普通倒计时一样
+        _ if counting_up => (
+            &format.up,
+            if relative {
+                i18n::t("countdown.relative")
+            } else {
+                i18n::tn("countdown.up", -remaining.num_days())
+            },
+            Style::fg(Color::Green),
+            number,
+        ),
+        86401_i64..=i64::MAX => (
+            &format.far,
+            if relative {
+                i18n::t("countdown.relative")
+            } else {
+                i18n::tn("countdown.far", remaining.num_days())
+            },
+            Style::fg(Color::Magenta),
+            number,
+        ),
+        1_i64..=86400 => (
+            &format.day,
+            if relative {
+                i18n::t("countdown.relative")
+            } else {
+                i18n::t("countdown.near")
+            },
+            Style::fg(Color::Red),
+            number,
+        ),
+        0 => (
+            &format.now,
+            i18n::t("countdown.now"),
+            Style::default(),
+            Style::default(),
+        ),
+        i64::MIN..=-1_i64 => (
+            &format.past,
+            i18n::t("countdown.relative"),
+            Style::default(),
+            Style::default(),
+        ),
+    };
+    let values = CountdownValues {
+        title,
+        target: target.at,
+        remaining,
+        with_ms: !counting_up && remaining.num_seconds() <= 86400,
+    };
+    let template = custom.as_deref().unwrap_or(default);
+    let with_ms = values.with_ms
+        && template::parse(template)
+            .iter()
+            .any(|piece| matches!(piece, Piece::Field("clock" | "ms", _)));
+    let mut line = template_line(template, |name, spec| {
+        let style = match name {
+            "title" => title_style,
+            "target" => Style::default(),
+            _ => number_style,
+        };
+        values.get(name, spec).map(|text| Span::styled(text, style))
+    });
+
+    if !target.zone.is_local() {
+        let zone_time = target
+            .zone
+            .wall_time_of(target.at)
+            .format("%Y-%m-%d %H:%M:%S");
+        let local_time = target.at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S");
+        line.push(Span::styled(
+            i18n::tf(
+                "countdown.zone",
+                &[
+                    ("zone_time", &zone_time),
+                    ("zone", &target.zone.name()),
+                    ("local_time", &local_time),
+                ],
+            ),
+            Style::fg(Color::DarkGrey),
+        ));
+    }
+    (line, with_ms)
+}
@@ -0,0 +1,215 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::config::{describe_duration, CountDownData, FormatConfig, Mode, Target};
+use crate::i18n;
+use crate::notify::Notifiers;
+use crate::recurrence::Milestone;
+use crate::state::FireLog;
+
+pub struct Scheduled {
+    pub title: String,
+    pub target: Target,
+    pub lead_times: Vec<Duration>,
+    pub format: FormatConfig,
+    pub mode: Mode,
+    pub milestones: Vec<Milestone>,
+    pub repeating: bool,
+}
+
+// 某个里程碑最近一次越过时的序号和时间，以及下一次的时间
+struct MilestoneTimes<'a> {
+    index: usize,
+    milestone: &'a Milestone,
+    count: i64,
+    last: Option<DateTime<Utc>>,
+    next: Option<DateTime<Utc>>,
+}
+
+impl Scheduled {
+    fn milestone_times(&self, now: DateTime<Utc>) -> impl Iterator<Item = MilestoneTimes<'_>> {
+        let zone = self.target.zone;
+        let origin = zone.wall_time_of(self.target.at);
+        let wall_now = zone.wall_time_of(now);
+        self.milestones
+            .iter()
+            .enumerate()
+            .map(move |(index, milestone)| {
+                let count = milestone.crossed(origin, wall_now);
+                let at = |count| {
+                    milestone
+                        .nth(origin, count)
+                        .and_then(|wall| zone.instant_of(wall))
+                };
+                MilestoneTimes {
+                    index,
+                    milestone,
+                    count,
+                    last: at(count),
+                    next: at(count + 1),
+                }
+            })
+    }
+}
+
+// 算出所有启用的倒计时当前的目标时间并排序，配置在加载时已经检查过
+pub fn schedule(data: CountDownData, now: DateTime<Utc>) -> Vec<Scheduled> {
+    let mut scheduled: Vec<Scheduled> = data
+        .events
+        .into_iter()
+        .filter_map(|event| {
+            Some(Scheduled {
+                target: event.target(now).ok()?,
+                title: event.title,
+                lead_times: event.lead_times,
+                format: event.format,
+                mode: event.mode,
+                milestones: event.milestones,
+                repeating: event.repeat.is_some(),
+            })
+        })
+        .collect();
+    scheduled.sort_by_key(|entry| entry.target.at);
+    scheduled
+}
+
+// 下一个提醒或到点的时刻，在这之前不需要重新计算
+pub fn next_due(scheduled: &[Scheduled], now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    scheduled
+        .iter()
+        .flat_map(|entry| {
+            std::iter::once(entry.target.at)
+                .chain(
+                    entry
+                        .lead_times
+                        .iter()
+                        .filter_map(|lead| entry.target.at.checked_sub_signed(*lead)),
+                )
+                .chain(entry.milestone_times(now).filter_map(|times| times.next))
+                // 重复的条目到点后一秒滚到下一次，要在那时重新计算
+                .chain(
+                    entry
+                        .repeating
+                        .then(|| entry.target.at.checked_add_signed(Duration::seconds(1)))
+                        .flatten(),
+                )
+        })
+        .filter(|due| *due > now)
+        .min()
+}
+
+pub fn fire_due(
+    fire_log: &mut FireLog,
+    notifiers: &Notifiers,
+    scheduled: &[Scheduled],
+    now: DateTime<Utc>,
+) {
+    for entry in scheduled {
+        // 同时检查上一次到点的时间，避免跳过到点那一秒时漏发通知
+        for due in [Some(entry.target.at), entry.target.previous]
+            .into_iter()
+            .flatten()
+        {
+            if fire_log.fire_once(&entry.title, due, now) {
+                notifiers.send(&entry.title, "");
+            }
+        }
+        for lead in &entry.lead_times {
+            // 提前量超出时间范围的提醒永远不会到
+            let Some(due) = entry.target.at.checked_sub_signed(*lead) else {
+                continue;
+            };
+            // 提前量不短于重复周期时，提醒会和上一次到点同时弹出
+            if entry
+                .target
+                .previous
+                .is_some_and(|previous| due <= previous)
+            {
+                continue;
+            }
+            let key = format!("{}#{}", entry.title, lead.num_seconds());
+            if fire_log.fire_once(&key, due, now) {
+                let duration = describe_duration(*lead);
+                notifiers.send(
+                    &entry.title,
+                    &i18n::tf("remind.left", &[("duration", &duration)]),
+                );
+            }
+        }
+        for times in entry.milestone_times(now) {
+            let Some(due) = times.last else {
+                continue;
+            };
+            let key = format!("{}~{}", entry.title, times.index);
+            if fire_log.fire_once(&key, due, now) {
+                let duration = times.milestone.describe(times.count);
+                notifiers.send(
+                    &entry.title,
+                    &i18n::tf("milestone.reached", &[("duration", &duration)]),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(input: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(input)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn schedule_at(config: &str, now: DateTime<Utc>) -> Vec<Scheduled> {
+        schedule(CountDownData::load("test.toml", config).unwrap(), now)
+    }
+
+    fn temp_log(name: &str) -> FireLog {
+        let path = std::env::temp_dir()
+            .join(format!("countdown_cli-engine-{}", std::process::id()))
+            .join(name);
+        let _ = std::fs::remove_file(&path);
+        FireLog::open(path)
+    }
+
+    const DAILY: &str = r#"
+[defaults]
+remind = ["1d", "1h"]
+
+[[countdown]]
+title = "daily"
+datetime = "2025-01-01T12:00:00Z"
+repeat = "daily"
+"#;
+
+    #[test]
+    fn reminders_as_long_as_the_period_are_skipped() {
+        let now = at("2025-01-05T12:00:02Z");
+        let scheduled = schedule_at(DAILY, now);
+        let mut fire_log = temp_log("period.json");
+        fire_due(&mut fire_log, &Notifiers::default(), &scheduled, now);
+        // 到点的通知已经发过，明天的 1d 提醒没有跟着一起发
+        assert!(!fire_log.fire_once("daily", at("2025-01-05T12:00:00Z"), now));
+        assert!(fire_log.fire_once("daily#86400", at("2025-01-05T12:00:00Z"), now));
+
+        let now = at("2025-01-06T11:00:00Z");
+        let scheduled = schedule_at(DAILY, now);
+        fire_due(&mut fire_log, &Notifiers::default(), &scheduled, now);
+        assert!(!fire_log.fire_once("daily#3600", at("2025-01-06T11:00:00Z"), now));
+    }
+
+    #[test]
+    fn next_due_includes_the_roll_over_of_repeating_entries() {
+        let config = format!(
+            "{DAILY}
+[[countdown]]
+title = \"far\"
+datetime = \"2099-01-01T00:00:00Z\"
+"
+        );
+        let now = at("2025-01-05T12:00:00Z");
+        let scheduled = schedule_at(&config, now);
+        assert_eq!(next_due(&scheduled, now), Some(at("2025-01-05T12:00:01Z")));
+    }
+}
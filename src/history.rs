@@ -0,0 +1,247 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Utc};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::i18n;
+use crate::pomodoro::PomodoroState;
+use crate::state::state_dir;
+
+const HISTORY_FILE: &str = "history.jsonl";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhaseRecord {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub phase: PomodoroState,
+    pub planned_secs: u64,
+    pub actual_secs: u64,
+    pub completed: bool,
+    #[serde(default)]
+    pub task: Option<String>,
+    #[serde(default)]
+    pub pause_count: u32,
+    #[serde(default)]
+    pub paused_secs: u64,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+pub fn append(record: &PhaseRecord) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = state_dir();
+    std::fs::create_dir_all(&dir)?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(HISTORY_FILE))?;
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+    Ok(())
+}
+
+pub fn load() -> Vec<PhaseRecord> {
+    let Ok(file) = std::fs::File::open(state_dir().join(HISTORY_FILE)) else {
+        return Vec::new();
+    };
+    std::io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+fn local_date(datetime: DateTime<Utc>) -> NaiveDate {
+    datetime.with_timezone(&Local).date_naive()
+}
+
+// 所在 ISO 周的周一
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday().into())
+}
+
+fn history_row(label: &dyn std::fmt::Display, secs: u64, completed: u32) -> String {
+    i18n::tf(
+        "history.row",
+        &[
+            ("label", label),
+            ("focus", &format_minutes(secs)),
+            ("completed", &completed),
+        ],
+    )
+}
+
+fn format_minutes(secs: u64) -> String {
+    format!("{}h{:02}m", secs / 3600, secs % 3600 / 60)
+}
+
+pub fn export(
+    records: &[PhaseRecord],
+    format: ExportFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match format {
+        ExportFormat::Json => println!("{}", serde_json::to_string_pretty(records)?),
+        ExportFormat::Csv => {
+            println!(
+                "start,end,phase,planned_secs,actual_secs,completed,pause_count,paused_secs,task"
+            );
+            for record in records {
+                let task = record.task.clone().unwrap_or_default().replace('"', "\"\"");
+                println!(
+                    "{},{},{:?},{},{},{},{},{},\"{task}\"",
+                    record.start.to_rfc3339(),
+                    record.end.to_rfc3339(),
+                    record.phase,
+                    record.planned_secs,
+                    record.actual_secs,
+                    record.completed,
+                    record.pause_count,
+                    record.paused_secs
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn report(records: &[PhaseRecord]) {
+    let work: Vec<&PhaseRecord> = records
+        .iter()
+        .filter(|record| record.phase == PomodoroState::Work)
+        .collect();
+    if work.is_empty() {
+        println!("{}", i18n::t("history.empty"));
+        return;
+    }
+
+    // 按天、按周汇总专注时间和完成数
+    let mut days: BTreeMap<NaiveDate, (u64, u32)> = BTreeMap::new();
+    let mut weeks: BTreeMap<NaiveDate, (u64, u32)> = BTreeMap::new();
+    for record in &work {
+        let date = local_date(record.start);
+        let completed = u32::from(record.completed);
+        let day = days.entry(date).or_default();
+        day.0 += record.actual_secs;
+        day.1 += completed;
+        let week = weeks.entry(week_start(date)).or_default();
+        week.0 += record.actual_secs;
+        week.1 += completed;
+    }
+
+    // 按日历取到今天为止的最近几天、几周，没有记录的显示为 0
+    let today = Local::now().date_naive();
+    println!("{}", i18n::t("history.daily"));
+    for date in (0..7).rev().map(|ago| today - Duration::days(ago)) {
+        let (secs, completed) = days.get(&date).copied().unwrap_or_default();
+        println!("{}", history_row(&date, secs, completed));
+    }
+    println!("{}", i18n::t("history.weekly"));
+    for monday in (0..4)
+        .rev()
+        .map(|ago| week_start(today) - Duration::weeks(ago))
+    {
+        let (secs, completed) = weeks.get(&monday).copied().unwrap_or_default();
+        let week = monday.iso_week();
+        let label = format!("{}-W{:02}", week.year(), week.week());
+        println!("{}", history_row(&label, secs, completed));
+    }
+
+    let completed: Vec<&&PhaseRecord> = work.iter().filter(|record| record.completed).collect();
+    let average = if completed.is_empty() {
+        0
+    } else {
+        completed
+            .iter()
+            .map(|record| record.actual_secs)
+            .sum::<u64>()
+            / completed.len() as u64
+    };
+    let (current, longest) = streaks(&days, today);
+    let rate = format!("{:.0}", completed.len() as f64 * 100.0 / work.len() as f64);
+    println!(
+        "{}",
+        i18n::tf("history.average", &[("focus", &format_minutes(average))])
+    );
+    println!(
+        "{}",
+        i18n::tf(
+            "history.completion",
+            &[
+                ("rate", &rate),
+                ("completed", &completed.len()),
+                ("total", &work.len()),
+            ],
+        )
+    );
+    let days = |count: u32| i18n::tfn("unit.day", count.into(), &[("count", &count)]);
+    println!(
+        "{}",
+        i18n::tf(
+            "history.streak",
+            &[("current", &days(current)), ("longest", &days(longest))],
+        )
+    );
+}
+
+// 连续有完成番茄的天数：当前连续和历史最长
+fn streaks(days: &BTreeMap<NaiveDate, (u64, u32)>, today: NaiveDate) -> (u32, u32) {
+    let active: Vec<NaiveDate> = days
+        .iter()
+        .filter(|(_, (_, completed))| *completed > 0)
+        .map(|(date, _)| *date)
+        .collect();
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for date in &active {
+        run = match previous {
+            Some(previous) if *date - previous == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(*date);
+    }
+    let current = match previous {
+        Some(last) if today - last <= Duration::days(1) => run,
+        _ => 0,
+    };
+    (current, longest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(input: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(input, "%Y-%m-%d").unwrap()
+    }
+
+    fn active(dates: &[&str]) -> BTreeMap<NaiveDate, (u64, u32)> {
+        dates.iter().map(|date| (day(date), (1500, 1))).collect()
+    }
+
+    #[test]
+    fn streaks_break_on_gaps() {
+        let days = active(&["2024-03-01", "2024-03-02", "2024-03-03", "2024-03-05"]);
+        assert_eq!(streaks(&days, day("2024-03-05")), (1, 3));
+    }
+
+    #[test]
+    fn streak_continues_until_today_ends() {
+        let days = active(&["2024-03-04", "2024-03-05"]);
+        assert_eq!(streaks(&days, day("2024-03-05")), (2, 2));
+        assert_eq!(streaks(&days, day("2024-03-06")), (2, 2));
+        assert_eq!(streaks(&days, day("2024-03-07")), (0, 2));
+    }
+
+    #[test]
+    fn unfinished_days_do_not_count() {
+        let mut days = active(&["2024-03-04", "2024-03-06"]);
+        days.insert(day("2024-03-05"), (600, 0));
+        assert_eq!(streaks(&days, day("2024-03-06")), (1, 1));
+        assert_eq!(streaks(&BTreeMap::new(), day("2024-03-06")), (0, 0));
+    }
+}
@@ -0,0 +1,163 @@
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDateTime, Utc};
+
+use crate::i18n;
+
+// 日历上的差值：按实际月份长度数出整月，剩下的是时长
+fn calendar_diff(from: NaiveDateTime, to: NaiveDateTime) -> (u32, Duration) {
+    let add = |months: i32| from.checked_add_months(Months::new(months as u32));
+    let mut months = (to.year() - from.year()) * 12 + to.month() as i32 - from.month() as i32;
+    while months > 0 && add(months).is_none_or(|date| date > to) {
+        months -= 1;
+    }
+    let months = months.max(0);
+    (months as u32, to - add(months).unwrap_or(from))
+}
+
+// 取最大的两个相邻单位，例如 "1 year 2 months"、"3 weeks 2 days"
+pub fn duration_between(from: NaiveDateTime, to: NaiveDateTime) -> String {
+    let (months, rest) = calendar_diff(from, to);
+    let days = rest.num_days();
+    let units = [
+        (i64::from(months / 12), "unit.year"),
+        (i64::from(months % 12), "unit.month"),
+        (days / 7, "unit.week"),
+        (days % 7, "unit.day"),
+        (rest.num_hours() % 24, "unit.hour"),
+        (rest.num_minutes() % 60, "unit.minute"),
+        (rest.num_seconds() % 60, "unit.second"),
+    ];
+    let Some(first) = units.iter().position(|(value, _)| *value > 0) else {
+        return i18n::tfn("unit.second", 0, &[("count", &0)]);
+    };
+    let parts: Vec<String> = units[first..]
+        .iter()
+        .take(2)
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| i18n::tfn(unit, *value, &[("count", value)]))
+        .collect();
+    parts.join(i18n::t("unit.separator"))
+}
+
+// 相对现在的描述，按本地日历计算：昨天、今天、明天显示钟点，其余显示时长
+pub fn relative(target: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    relative_wall(
+        target.with_timezone(&Local).naive_local(),
+        now.with_timezone(&Local).naive_local(),
+        target - now,
+    )
+}
+
+// delta 用真实时间差，夏令时切换时墙上时间的差值不准
+fn relative_wall(target: NaiveDateTime, now: NaiveDateTime, delta: Duration) -> String {
+    if delta.num_seconds() == 0 {
+        return i18n::t("relative.now").to_string();
+    }
+    if delta.num_hours() != 0 {
+        let key = match (target.date() - now.date()).num_days() {
+            -1 => Some("relative.yesterday"),
+            0 => Some("relative.today"),
+            1 => Some("relative.tomorrow"),
+            _ => None,
+        };
+        if let Some(key) = key {
+            return i18n::tf(key, &[("time", &target.format("%H:%M"))]);
+        }
+    }
+    if delta > Duration::zero() {
+        let duration = duration_between(now, target);
+        i18n::tf("relative.future", &[("duration", &duration)])
+    } else {
+        let duration = duration_between(target, now);
+        i18n::tf("relative.past", &[("duration", &duration)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(input: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn wall(target: &str, now: &str) -> String {
+        let (target, now) = (at(target), at(now));
+        relative_wall(target, now, target - now)
+    }
+
+    #[test]
+    fn calendar_diff_uses_real_month_lengths() {
+        assert_eq!(
+            calendar_diff(at("2023-01-31 00:00"), at("2023-03-01 00:00")),
+            (1, Duration::days(1))
+        );
+        assert_eq!(
+            calendar_diff(at("2024-01-31 00:00"), at("2024-03-01 00:00")),
+            (1, Duration::days(1))
+        );
+        assert_eq!(
+            calendar_diff(at("2024-01-15 12:00"), at("2024-02-15 11:00")),
+            (0, Duration::days(31) - Duration::hours(1))
+        );
+    }
+
+    #[test]
+    fn leap_day_across_non_leap_year() {
+        assert_eq!(
+            calendar_diff(at("2024-02-29 00:00"), at("2025-02-28 00:00")),
+            (12, Duration::zero())
+        );
+        assert_eq!(
+            duration_between(at("2024-02-29 00:00"), at("2025-02-28 00:00")),
+            "1 year"
+        );
+        assert_eq!(
+            duration_between(at("2024-02-29 00:00"), at("2025-03-29 00:00")),
+            "1 year 1 month"
+        );
+    }
+
+    #[test]
+    fn duration_between_shows_two_adjacent_units() {
+        assert_eq!(
+            duration_between(at("2024-05-01 00:00"), at("2024-05-24 05:00")),
+            "3 weeks 2 days"
+        );
+        // 相邻的周为 0 时不再往下取天
+        assert_eq!(
+            duration_between(at("2024-05-01 00:00"), at("2024-06-04 00:00")),
+            "1 month"
+        );
+        assert_eq!(
+            duration_between(at("2024-05-01 00:00"), at("2024-05-01 00:00")),
+            "0 seconds"
+        );
+    }
+
+    #[test]
+    fn relative_names_nearby_days() {
+        assert_eq!(
+            wall("2024-05-02 18:00", "2024-05-01 23:30"),
+            "tomorrow at 18:00"
+        );
+        assert_eq!(
+            wall("2024-05-01 18:00", "2024-05-02 08:00"),
+            "yesterday at 18:00"
+        );
+        assert_eq!(
+            wall("2024-05-01 18:00", "2024-05-01 09:00"),
+            "today at 18:00"
+        );
+        // 不到一小时时显示时长，即使已经跨过午夜
+        assert_eq!(
+            wall("2024-05-02 00:15", "2024-05-01 23:30"),
+            "in 45 minutes"
+        );
+        assert_eq!(
+            wall("2024-05-11 09:00", "2024-05-01 09:00"),
+            "in 1 week 3 days"
+        );
+        assert_eq!(wall("2024-04-01 09:00", "2024-05-01 09:00"), "1 month ago");
+        assert_eq!(wall("2024-05-01 09:00", "2024-05-01 09:00"), "now");
+    }
+}
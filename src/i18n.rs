@@ -0,0 +1,341 @@
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use serde_derive::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, clap::ValueEnum)]
+pub enum Lang {
+    #[serde(rename = "en")]
+    #[value(name = "en")]
+    En,
+    #[serde(rename = "zh-CN", alias = "zh")]
+    #[value(name = "zh-CN", alias = "zh")]
+    ZhCn,
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(Lang::En as u8);
+
+impl Lang {
+    // 按 LC_ALL、LC_MESSAGES、LANG 的顺序推断，例如 zh_CN.UTF-8
+    pub fn from_env() -> Option<Lang> {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty() && value != "C" && value != "POSIX")
+            .map(|value| {
+                if value.starts_with("zh") {
+                    Lang::ZhCn
+                } else {
+                    Lang::En
+                }
+            })
+    }
+
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Lang::En => EN,
+            Lang::ZhCn => ZH_CN,
+        }
+    }
+}
+
+// 优先级：命令行 > 配置文件 > 环境变量 > 英文
+pub fn select(cli: Option<Lang>, config: Option<Lang>) -> Lang {
+    cli.or(config).or_else(Lang::from_env).unwrap_or(Lang::En)
+}
+
+pub fn set_lang(lang: Lang) {
+    CURRENT.store(lang as u8, Ordering::Relaxed);
+}
+
+pub fn lang() -> Lang {
+    match CURRENT.load(Ordering::Relaxed) {
+        value if value == Lang::ZhCn as u8 => Lang::ZhCn,
+        _ => Lang::En,
+    }
+}
+
+fn lookup(key: &str) -> Option<&'static str> {
+    let find = |lang: Lang| {
+        lang.catalog()
+            .iter()
+            .find(|(name, _)| *name == key)
+            .map(|(_, text)| *text)
+    };
+    find(lang()).or_else(|| find(Lang::En))
+}
+
+pub fn t(key: &'static str) -> &'static str {
+    lookup(key).unwrap_or(key)
+}
+
+// 复数形式：英文 1 用 .one，其余用 .other；中文只有 .other
+pub fn tn(key: &'static str, count: i64) -> &'static str {
+    let category = match lang() {
+        Lang::En if count.abs() == 1 => "one",
+        _ => "other",
+    };
+    lookup(&format!("{key}.{category}"))
+        .or_else(|| lookup(&format!("{key}.other")))
+        .unwrap_or(key)
+}
+
+// 把模板中的 {name} 替换成参数
+pub fn fill(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut text = template.to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{name}}}"), &value.to_string());
+    }
+    text
+}
+
+pub fn tf(key: &'static str, args: &[(&str, &dyn Display)]) -> String {
+    fill(t(key), args)
+}
+
+pub fn tfn(key: &'static str, count: i64, args: &[(&str, &dyn Display)]) -> String {
+    fill(tn(key, count), args)
+}
+
+const EN: &[(&str, &str)] = &[
+    ("error.open_file", "Error: Cannot open file '{file}': {error}"),
+    ("error.tui", "Error: cannot start terminal UI: {error}"),
+    ("error.signal_handler", "Error: cannot set up signal handler: {error}"),
+    ("error.problems.one", "{count} problem found"),
+    ("error.problems.other", "{count} problems found"),
+    ("error.daemon_rejected", "daemon rejected the command"),
+    ("daemon.running", "daemon already running on {socket}"),
+    ("daemon.not_socket", "{socket} exists and is not a socket"),
+    ("daemon.listening", "daemon listening on {socket}"),
+    ("daemon.connect", "cannot connect to daemon at {socket}: {error}"),
+    ("manage.not_array", "'countdown' is not an array of tables"),
+    ("manage.exists", "countdown '{title}' already exists"),
+    ("manage.not_found", "countdown '{title}' not found"),
+    ("manage.added", "added '{title}' at {datetime}"),
+    ("manage.removed", "removed '{title}'"),
+    ("manage.enabled", "enabled '{title}'"),
+    ("manage.disabled", "disabled '{title}'"),
+    ("manage.ok.one", "{file}: ok, {count} countdown"),
+    ("manage.ok.other", "{file}: ok, {count} countdowns"),
+    ("history.empty", "No pomodoro history yet."),
+    ("history.daily", "Daily focus (last 7 days):"),
+    ("history.weekly", "Weekly focus (last 4 weeks):"),
+    ("history.row", "  {label}  {focus}  {completed} completed"),
+    ("history.average", "Average focus time: {focus}"),
+    ("history.completion", "Completion rate: {rate}% ({completed}/{total})"),
+    ("history.streak", "Current streak: {current}, longest streak: {longest}"),
+    ("config.invalid_datetime", "Error: the datetime '{datetime}' of '{title}' is invalid. {formats}"),
+    ("config.invalid_when", "Error: cannot understand the time '{datetime}'. {formats}"),
+    (
+        "config.datetime_formats",
+        "Supported formats: 2025-07-12 18:00:00, 2025-07-12 18:00, 2025-07-12, 2025-07-12T18:00:00+08:00, 18:00 (next occurrence); when adding also +2h30m, today 18:00, tomorrow 18:00 and next friday 18:00.",
+    ),
+    ("config.invalid_timezone", "Error: the time zone '{zone}' of '{title}' is invalid."),
+    ("config.invalid_repeat", "Error: the repeat rule of '{title}' is invalid: {error}"),
+    ("config.invalid_repeat_rule", "invalid repeat rule '{rule}': {error}"),
+    ("config.missing_datetime", "Error: the datetime of '{title}' does not exist in its time zone."),
+    ("config.invalid_start", "Error: the start time '{start}' of '{title}' is invalid."),
+    ("config.missing_start", "Error: the start time of '{title}' does not exist in its time zone."),
+    ("config.invalid_remind", "Error: the reminder '{remind}' of '{title}' is invalid."),
+    ("config.invalid_format", "Error: unknown placeholders in the format of '{title}': {fields}"),
+    ("config.countup_repeat", "Error: '{title}' counts up from its datetime and cannot repeat."),
+    ("config.milestones_need_countup", "Error: the milestones of '{title}' need mode = \"countup\"."),
+    ("config.invalid_milestone", "Error: a milestone of '{title}' is invalid: {error}"),
+    ("config.invalid_milestone_format", "'{milestone}' is not a milestone, use e.g. 'every 1000 days', 'every year' or '500d'"),
+    ("config.parse_error", "Error: {location}: {message}"),
+    ("config.entry_error", "countdown #{index}: {message}"),
+    ("config.unknown_sink", "Error: unknown notification sink '{sink}' in [notify] sinks."),
+    ("config.missing_sink_setting", "Error: the '{sink}' notification sink needs '{setting}' in [notify]."),
+    ("notify.failed", "Notification via '{sink}' failed: {error}"),
+    ("notify.exit_status", "'{command}' exited with {status}"),
+    ("notify.unavailable", "no desktop notification backend available"),
+    ("notify.linux_only", "freedesktop notifications are only supported on linux"),
+    ("notify.missing_sound", "sound file '{path}' does not exist"),
+    ("unit.year.one", "{count} year"),
+    ("unit.year.other", "{count} years"),
+    ("unit.month.one", "{count} month"),
+    ("unit.month.other", "{count} months"),
+    ("unit.week.one", "{count} week"),
+    ("unit.week.other", "{count} weeks"),
+    ("unit.day.one", "{count} day"),
+    ("unit.day.other", "{count} days"),
+    ("unit.hour.one", "{count} hour"),
+    ("unit.hour.other", "{count} hours"),
+    ("unit.minute.one", "{count} minute"),
+    ("unit.minute.other", "{count} minutes"),
+    ("unit.second.one", "{count} second"),
+    ("unit.second.other", "{count} seconds"),
+    ("unit.separator", " "),
+    ("relative.future", "in {duration}"),
+    ("relative.past", "{duration} ago"),
+    ("relative.now", "now"),
+    ("relative.today", "today at {time}"),
+    ("relative.tomorrow", "tomorrow at {time}"),
+    ("relative.yesterday", "yesterday at {time}"),
+    ("remind.left", "{duration} left"),
+    ("milestone.reached", "Milestone reached: {duration}"),
+    ("phase.Idle", "Idle"),
+    ("phase.Work", "Work"),
+    ("phase.ShortBreak", "Short break"),
+    ("phase.LongBreak", "Long break"),
+    ("pomodoro.not_started", "Pomodoro is not running"),
+    ("pomodoro.unknown_command", "Unknown command: {command}"),
+    ("pomodoro.invalid_number", "Invalid number: {value}"),
+    ("pomodoro.phase_end_notify", "Pomodoro: phase finished!"),
+    ("pomodoro.phase_end", "Phase finished! {next}"),
+    ("pomodoro.prompt_next", "Enter start/short/long to begin the next phase"),
+    ("pomodoro.auto_next", "Moving on to: {phase}"),
+    ("ui.pane.pomodoro", "Pomodoro"),
+    ("ui.pane.countdown", "Countdowns"),
+    ("ui.pane.help", "Help"),
+    ("ui.pane.diagnostics", "Problems (config errors keep the last good config)"),
+    ("ui.too_small", "Terminal window too small"),
+    ("ui.hint", "Type 'help' for commands, Ctrl-C to quit"),
+    ("ui.ok", "ok: {command}"),
+    ("ui.unknown_big_target", "Unknown display target: {target}"),
+    ("ui.idle_since", "Pomodoro is not running, {elapsed} since the last phase"),
+    ("ui.state", "Pomodoro: {phase}, remaining: {remaining}"),
+    ("ui.paused", " [paused]"),
+    ("ui.completed", "Completed work sessions: {count}"),
+    ("ui.task", "  Task: {task}"),
+    ("countdown.far.one", "{title}: There is {days} day, {clock} secs left."),
+    ("countdown.far.other", "{title}: There are {days} days, {clock} secs left."),
+    ("countdown.near", "{title}: There are {clock} secs left."),
+    ("countdown.now", "{title}: Now is the time!"),
+    ("countdown.relative", "{title}: {relative}"),
+    ("countdown.up.one", "{title}: {days} day, {clock} since {target:%Y-%m-%d}"),
+    ("countdown.up.other", "{title}: {days} days, {clock} since {target:%Y-%m-%d}"),
+    ("countdown.zone", " [{zone_time} {zone} / {local_time} local]"),
+    ("help.title", "Commands:"),
+    ("help.start", "start - start a work phase"),
+    ("help.start_task", "start <task> - start a work phase for a named task"),
+    ("help.short", "short - start a short break"),
+    ("help.long", "long - start a long break"),
+    ("help.stop", "stop - stop the pomodoro"),
+    ("help.next", "next - switch to the next phase"),
+    ("help.pause", "pause - pause the current phase"),
+    ("help.resume", "resume - resume the current phase"),
+    ("help.work", "work <minutes> - set the work duration"),
+    ("help.short_minutes", "short <minutes> - set the short break duration"),
+    ("help.long_minutes", "long <minutes> - set the long break duration"),
+    ("help.interval", "interval <count> - set the long break interval (work sessions)"),
+    ("help.big", "big [pomodoro|countdown|off] - toggle the large digit display"),
+    ("help.help", "help - show this help"),
+    ("help.quit", "quit - exit (or Ctrl-C)"),
+];
+
+const ZH_CN: &[(&str, &str)] = &[
+    ("error.open_file", "错误：无法打开文件 '{file}'：{error}"),
+    ("error.tui", "错误：无法启动终端界面：{error}"),
+    ("error.signal_handler", "错误：无法设置信号处理：{error}"),
+    ("error.problems.other", "发现 {count} 个问题"),
+    ("daemon.running", "守护进程已经在 {socket} 上运行"),
+    ("daemon.not_socket", "{socket} 已存在且不是 socket 文件"),
+    ("daemon.listening", "守护进程监听于 {socket}"),
+    ("daemon.connect", "无法连接到 {socket} 上的守护进程：{error}"),
+    ("manage.not_array", "'countdown' 不是表数组"),
+    ("manage.exists", "倒计时 '{title}' 已存在"),
+    ("manage.not_found", "找不到倒计时 '{title}'"),
+    ("manage.added", "已添加 '{title}'，时间 {datetime}"),
+    ("manage.removed", "已删除 '{title}'"),
+    ("manage.enabled", "已启用 '{title}'"),
+    ("manage.disabled", "已停用 '{title}'"),
+    ("manage.ok.other", "{file}：没有问题，共 {count} 个倒计时"),
+    ("history.empty", "还没有番茄钟记录。"),
+    ("history.daily", "每日专注（最近 7 天）："),
+    ("history.weekly", "每周专注（最近 4 周）："),
+    ("history.row", "  {label}  {focus}  完成 {completed} 个"),
+    ("history.average", "平均专注时间：{focus}"),
+    ("history.completion", "完成率：{rate}%（{completed}/{total}）"),
+    ("history.streak", "当前连续：{current}，最长连续：{longest}"),
+    ("error.daemon_rejected", "守护进程拒绝了该命令"),
+    ("config.invalid_datetime", "错误：'{title}' 的日期时间 '{datetime}' 无效。{formats}"),
+    ("config.invalid_when", "错误：无法识别时间 '{datetime}'。{formats}"),
+    (
+        "config.datetime_formats",
+        "支持的格式：2025-07-12 18:00:00、2025-07-12 18:00、2025-07-12、2025-07-12T18:00:00+08:00、18:00（下一次到达该时刻）；add 时还可以用 +2h30m、today 18:00、tomorrow 18:00、next friday 18:00。",
+    ),
+    ("config.invalid_timezone", "错误：'{title}' 的时区 '{zone}' 无效。"),
+    ("config.invalid_repeat", "错误：'{title}' 的重复规则无效：{error}"),
+    ("config.invalid_repeat_rule", "无效的重复规则 '{rule}'：{error}"),
+    ("config.missing_datetime", "错误：'{title}' 的日期时间在时区中不存在。"),
+    ("config.invalid_start", "错误：'{title}' 的开始时间 '{start}' 格式无效。"),
+    ("config.missing_start", "错误：'{title}' 的开始时间在时区中不存在。"),
+    ("config.invalid_remind", "错误：'{title}' 的提醒时间 '{remind}' 无效。"),
+    ("config.invalid_format", "错误：'{title}' 的显示模板中有无法识别的变量：{fields}"),
+    ("config.countup_repeat", "错误：'{title}' 是正计时，不能设置重复规则。"),
+    ("config.milestones_need_countup", "错误：'{title}' 设置了里程碑，需要 mode = \"countup\"。"),
+    ("config.invalid_milestone", "错误：'{title}' 的里程碑无效：{error}"),
+    ("config.invalid_milestone_format", "无法识别里程碑 '{milestone}'，可以写成 'every 1000 days'、'every year' 或 '500d'"),
+    ("config.parse_error", "错误：{location}：{message}"),
+    ("config.entry_error", "第 {index} 个倒计时：{message}"),
+    ("config.unknown_sink", "错误：[notify] sinks 中有未知的通知通道 '{sink}'。"),
+    ("config.missing_sink_setting", "错误：通知通道 '{sink}' 需要在 [notify] 中设置 '{setting}'。"),
+    ("notify.failed", "通过 '{sink}' 发送通知失败：{error}"),
+    ("notify.exit_status", "'{command}' 退出状态为 {status}"),
+    ("notify.unavailable", "没有可用的桌面通知方式"),
+    ("notify.linux_only", "freedesktop 通知只支持 Linux"),
+    ("notify.missing_sound", "声音文件 '{path}' 不存在"),
+    ("unit.year.other", "{count}年"),
+    ("unit.month.other", "{count}个月"),
+    ("unit.week.other", "{count}周"),
+    ("unit.day.other", "{count}天"),
+    ("unit.hour.other", "{count}小时"),
+    ("unit.minute.other", "{count}分钟"),
+    ("unit.second.other", "{count}秒"),
+    ("unit.separator", ""),
+    ("relative.future", "{duration}后"),
+    ("relative.past", "{duration}前"),
+    ("relative.now", "现在"),
+    ("relative.today", "今天 {time}"),
+    ("relative.tomorrow", "明天 {time}"),
+    ("relative.yesterday", "昨天 {time}"),
+    ("remind.left", "还剩 {duration}"),
+    ("milestone.reached", "达成里程碑：{duration}"),
+    ("phase.Idle", "空闲"),
+    ("phase.Work", "工作"),
+    ("phase.ShortBreak", "短休息"),
+    ("phase.LongBreak", "长休息"),
+    ("pomodoro.not_started", "番茄钟未启动"),
+    ("pomodoro.unknown_command", "未知命令: {command}"),
+    ("pomodoro.invalid_number", "无效的数字: {value}"),
+    ("pomodoro.phase_end_notify", "番茄钟：当前阶段结束！"),
+    ("pomodoro.phase_end", "当前阶段结束！{next}"),
+    ("pomodoro.prompt_next", "请输入下一个命令（start/short/long）来开始新的阶段"),
+    ("pomodoro.auto_next", "自动进入下一阶段: {phase}"),
+    ("ui.pane.pomodoro", "番茄钟"),
+    ("ui.pane.countdown", "倒计时"),
+    ("ui.pane.help", "帮助"),
+    ("ui.pane.diagnostics", "问题（配置有误时继续使用上次有效的配置）"),
+    ("ui.too_small", "终端窗口太小"),
+    ("ui.hint", "输入 'help' 查看可用命令，Ctrl-C 退出"),
+    ("ui.ok", "已执行: {command}"),
+    ("ui.unknown_big_target", "未知的显示目标: {target}"),
+    ("ui.idle_since", "番茄钟未启动，上次完成后已经过去: {elapsed}"),
+    ("ui.state", "番茄钟状态: {phase}, 剩余时间: {remaining}"),
+    ("ui.paused", " [已暂停]"),
+    ("ui.completed", "已完成的工作周期: {count}"),
+    ("ui.task", "  任务: {task}"),
+    ("countdown.far.other", "{title}：还有 {days} 天 {clock}"),
+    ("countdown.near", "{title}：还剩 {clock}"),
+    ("countdown.now", "{title}：时间到了！"),
+    ("countdown.relative", "{title}：{relative}"),
+    ("countdown.up.other", "{title}：已经 {days} 天 {clock}（自 {target:%Y-%m-%d}）"),
+    ("countdown.zone", " [{zone_time} {zone} / 本地 {local_time}]"),
+    ("help.title", "可用命令："),
+    ("help.start", "start - 开始工作阶段"),
+    ("help.start_task", "start <任务> - 开始工作阶段并记录任务名称"),
+    ("help.short", "short - 开始短休息阶段"),
+    ("help.long", "long - 开始长休息阶段"),
+    ("help.stop", "stop - 停止番茄钟"),
+    ("help.next", "next - 手动切换到下一个状态"),
+    ("help.pause", "pause - 暂停当前阶段计时"),
+    ("help.resume", "resume - 继续当前阶段计时"),
+    ("help.work", "work <分钟> - 设置工作时间"),
+    ("help.short_minutes", "short <分钟> - 设置短休息时间"),
+    ("help.long_minutes", "long <分钟> - 设置长休息时间"),
+    ("help.interval", "interval <次数> - 设置长休息间隔（工作周期次数）"),
+    ("help.big", "big [pomodoro|countdown|off] - 切换大号数字显示"),
+    ("help.help", "help - 显示此帮助信息"),
+    ("help.quit", "quit - 退出（或 Ctrl-C）"),
+];
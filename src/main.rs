@@ -1,22 +1,40 @@
-use chrono::{Local, NaiveDateTime};
-use clap::Parser;
-use colored::*;
+use bigdigits::BigTarget;
+use chrono::Utc;
+use clap::{Parser, Subcommand};
 use config::{CountDownConfig, HotReload};
-use crossterm::terminal::{Clear, ClearType};
-use crossterm::{cursor, ExecutableCommand};
-use notify::osx_terminal_notifier;
-use std::io::{stdout, Write};
+use crossterm::event::{self, Event};
+use dashboard::{Action, Dashboard};
+use history::ExportFormat;
+use i18n::Lang;
+use output::OutputFormat;
+use pomodoro::{PomodoroState, PomodoroTimer};
+use scheduler::Scheduler;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc as std_mpsc;
 use std::sync::Arc;
 use std::thread;
-use std::time::{Duration as StdDuration, Duration, Instant};
+use std::time::Duration;
+use tokio::sync::mpsc as tokio_mpsc;
 use tokio::sync::Mutex;
-use tokio::time::sleep;
+use tui::Terminal;
 
-mod command;
+mod bigdigits;
 mod config;
+mod daemon;
+mod dashboard;
+mod engine;
+mod history;
+mod humanize;
+mod i18n;
+mod manage;
 mod notify;
+mod output;
+mod pomodoro;
+mod recurrence;
+mod reload;
+mod scheduler;
+mod state;
+mod template;
+mod tui;
 
 pub fn get_styles() -> clap::builder::Styles {
     use clap::builder::styling::*;
@@ -27,6 +45,11 @@ pub fn get_styles() -> clap::builder::Styles {
         .placeholder(AnsiColor::Green.on_default())
 }
 
+// 分钟数换算成秒时不能溢出
+fn minutes_parser() -> clap::builder::RangedU64ValueParser {
+    clap::value_parser!(u64).range(..=u64::MAX / 60)
+}
+
 #[derive(Parser, Debug)]
 #[command(author="dc38528<cdzone@yeah.net>", version, about="terminal cli countdown widget.", long_about = None, styles=get_styles())]
 struct CliArgs {
@@ -38,395 +61,346 @@ struct CliArgs {
     config_file: String,
     #[arg(short = 's', long = "notify_sound", default_value = "")]
     notify_sound: Option<String>,
-    #[arg(long = "work", help = "Set work duration in minutes")]
+    #[arg(long = "work", help = "Set work duration in minutes", value_parser = minutes_parser())]
     work_duration: Option<u64>,
-    #[arg(long = "short-break", help = "Set short break duration in minutes")]
+    #[arg(
+        long = "short-break",
+        help = "Set short break duration in minutes",
+        value_parser = minutes_parser()
+    )]
     short_break_duration: Option<u64>,
-    #[arg(long = "long-break", help = "Set long break duration in minutes")]
+    #[arg(
+        long = "long-break",
+        help = "Set long break duration in minutes",
+        value_parser = minutes_parser()
+    )]
     long_break_duration: Option<u64>,
     #[arg(
         long = "interval",
-        help = "Set long break interval (number of work sessions)"
+        help = "Set long break interval (number of work sessions)",
+        value_parser = clap::value_parser!(u32).range(1..)
     )]
     long_break_interval: Option<u32>,
+    #[arg(
+        long = "auto",
+        help = "Automatically advance work -> break -> work when a phase ends"
+    )]
+    auto_advance: bool,
+    #[arg(
+        long = "confirm-work",
+        help = "With --auto, wait for 'start' before each work phase"
+    )]
+    confirm_work: bool,
+    #[arg(
+        long = "big",
+        help = "Show the pomodoro or the nearest countdown in large digits"
+    )]
+    big: Option<BigTarget>,
+    #[arg(
+        long = "lang",
+        help = "UI language (en, zh-CN); defaults to config or LANG"
+    )]
+    lang: Option<Lang>,
+    #[arg(
+        long = "output",
+        help = "Print machine-readable state instead of the TUI: json (once) or ndjson (on change)"
+    )]
+    output: Option<OutputFormat>,
+    #[arg(
+        long = "daemon",
+        help = "Run headless and accept commands on a unix socket"
+    )]
+    daemon: bool,
+    #[arg(long = "socket", help = "Unix socket path used by --daemon and ctl")]
+    socket: Option<String>,
+    #[command(subcommand)]
+    command: Option<Commands>,
 }
 
-#[derive(Debug, PartialEq)]
-enum PomodoroState {
-    Idle,
-    Work,
-    ShortBreak,
-    LongBreak,
-}
-
-struct PomodoroTimer {
-    start_time: Option<Instant>,
-    work_duration: Duration,
-    short_break_duration: Duration,
-    long_break_duration: Duration,
-    state: PomodoroState,
-    completed_work_sessions: u32,
-    long_break_interval: u32,
-    last_completed_time: Option<Instant>,
-}
-
-impl PomodoroTimer {
-    fn new(cli_args: Option<&CliArgs>) -> Self {
-        // 使用命令行参数，如果没有则使用默认值
-        let work_duration = cli_args.and_then(|args| args.work_duration).unwrap_or(25);
-        let short_break_duration = cli_args
-            .and_then(|args| args.short_break_duration)
-            .unwrap_or(5);
-        let long_break_duration = cli_args
-            .and_then(|args| args.long_break_duration)
-            .unwrap_or(15);
-        let long_break_interval = cli_args
-            .and_then(|args| args.long_break_interval)
-            .unwrap_or(4);
-
-        PomodoroTimer {
-            start_time: None,
-            work_duration: Duration::from_secs(work_duration * 60),
-            short_break_duration: Duration::from_secs(short_break_duration * 60),
-            long_break_duration: Duration::from_secs(long_break_duration * 60),
-            state: PomodoroState::Idle,
-            completed_work_sessions: 0,
-            long_break_interval,
-            last_completed_time: None,
-        }
-    }
-
-    fn stop(&mut self) {
-        self.start_time = None;
-        self.state = PomodoroState::Idle;
-    }
-
-    fn remaining_time(&self) -> Option<Duration> {
-        self.start_time.map(|start| {
-            let elapsed = start.elapsed();
-            let duration = match self.state {
-                PomodoroState::Work => self.work_duration,
-                PomodoroState::ShortBreak => self.short_break_duration,
-                PomodoroState::LongBreak => self.long_break_duration,
-                PomodoroState::Idle => return Duration::from_secs(0),
-            };
-            if elapsed >= duration {
-                Duration::from_secs(0)
-            } else {
-                duration - elapsed
-            }
-        })
-    }
-
-    fn next_state(&mut self) {
-        match self.state {
-            PomodoroState::Work => {
-                self.completed_work_sessions += 1;
-                self.last_completed_time = Some(Instant::now());
-            }
-            PomodoroState::ShortBreak | PomodoroState::LongBreak => {
-                self.last_completed_time = Some(Instant::now());
-            }
-            PomodoroState::Idle => {}
-        }
-        self.state = PomodoroState::Idle;
-        self.start_time = None;
-    }
-
-    fn set_state(&mut self, new_state: PomodoroState) {
-        self.state = new_state;
-        self.start_time = Some(Instant::now());
-        self.last_completed_time = None; // 清除上次完成时间
-    }
-
-    fn set_work_duration(&mut self, minutes: u64) {
-        self.work_duration = Duration::from_secs(minutes * 60);
-    }
-
-    fn set_short_break_duration(&mut self, minutes: u64) {
-        self.short_break_duration = Duration::from_secs(minutes * 60);
-    }
-
-    fn set_long_break_duration(&mut self, minutes: u64) {
-        self.long_break_duration = Duration::from_secs(minutes * 60);
-    }
-
-    fn set_long_break_interval(&mut self, interval: u32) {
-        self.long_break_interval = interval;
-    }
-
-    fn time_since_last_completion(&self) -> Option<Duration> {
-        self.last_completed_time.map(|time| time.elapsed())
-    }
+#[derive(Subcommand, Debug)]
+enum Commands {
+    #[command(about = "Run the interactive countdown and pomodoro display (default)")]
+    Run,
+    #[command(about = "Print all countdowns and exit")]
+    List,
+    #[command(about = "Add a countdown to the config file")]
+    Add {
+        #[arg(long)]
+        title: String,
+        #[arg(
+            long,
+            help = "Target datetime, e.g. '2025-07-12 12:00:00', '2025-07-12', '18:00', '+2h30m' or 'next friday 18:00'"
+        )]
+        at: String,
+        #[arg(
+            long,
+            help = "Repeat rule: daily, weekdays, weekly, monthly, yearly or cron"
+        )]
+        repeat: Option<String>,
+        #[arg(long, help = "IANA time zone name, e.g. Europe/Berlin")]
+        timezone: Option<String>,
+    },
+    #[command(about = "Remove a countdown from the config file")]
+    Remove { title: String },
+    #[command(about = "Enable a countdown in the config file")]
+    Enable { title: String },
+    #[command(about = "Disable a countdown in the config file")]
+    Disable { title: String },
+    #[command(about = "Check the config file and report invalid entries")]
+    Validate,
+    #[command(about = "Show pomodoro statistics or export the session history")]
+    Stats {
+        #[arg(
+            long,
+            value_enum,
+            help = "Export the raw history instead of the report"
+        )]
+        export: Option<ExportFormat>,
+    },
+    #[command(about = "Print a one-line summary for tmux, i3blocks, waybar or starship")]
+    Status {
+        #[arg(
+            long,
+            default_value = output::DEFAULT_STATUS_FORMAT,
+            help = "Template with {pomodoro_state} {pomodoro_remaining} {pomodoro_task} {next_title} {next_remaining} {next_target}"
+        )]
+        format: String,
+        #[arg(long, help = "Print waybar JSON with text, tooltip and class")]
+        waybar: bool,
+    },
+    #[command(
+        about = "Send a command (start, stop, short, long, next, work <m>, status...) to the running daemon"
+    )]
+    Ctl {
+        #[arg(required = true, trailing_var_arg = true)]
+        args: Vec<String>,
+    },
 }
 
-#[allow(unused_assignments)]
 async fn terminal_run(
     if_running: Arc<AtomicBool>,
     config: CountDownConfig,
     notify_sound: Option<String>,
     cli_args: Option<&CliArgs>,
 ) {
-    let mut stdout = stdout();
-    let mut last_line_count = 0;
-    let pomodoro = Arc::new(Mutex::new(PomodoroTimer::new(cli_args)));
+    let mut terminal = match Terminal::enter() {
+        Ok(terminal) => terminal,
+        Err(err) => {
+            eprintln!("{}", i18n::tf("error.tui", &[("error", &err)]));
+            if_running.store(false, Ordering::SeqCst);
+            return;
+        }
+    };
+    let mut dashboard = Dashboard::new(cli_args.and_then(|args| args.big));
+    let pomodoro = Arc::new(Mutex::new(PomodoroTimer::load(cli_args)));
 
-    let (tx, rx) = std_mpsc::channel();
+    let (tx, mut rx) = tokio_mpsc::unbounded_channel();
 
     let if_running_clone = if_running.clone();
     thread::spawn(move || {
-        handle_user_input(tx, if_running_clone);
+        handle_terminal_events(tx, if_running_clone);
     });
 
-    let mut paused = false;
-    let mut clean_without_output = false;
+    let mut scheduler = Scheduler::new(config, notify_sound, cli_args.and_then(|args| args.lang));
 
     while if_running.load(Ordering::SeqCst) {
-        if let Ok(command) = rx.try_recv() {
-            let mut pomodoro_lock = pomodoro.lock().await;
-            match command
-                .as_str()
-                .split_whitespace()
-                .collect::<Vec<_>>()
-                .as_slice()
-            {
-                ["start"] => pomodoro_lock.set_state(PomodoroState::Work),
-                ["stop"] => pomodoro_lock.stop(),
-                ["short"] => pomodoro_lock.set_state(PomodoroState::ShortBreak),
-                ["long"] => pomodoro_lock.set_state(PomodoroState::LongBreak),
-                ["next"] => pomodoro_lock.next_state(),
-                ["work", duration] => {
-                    if let Ok(minutes) = duration.parse() {
-                        pomodoro_lock.set_work_duration(minutes);
-                    }
-                }
-                ["short", duration] => {
-                    if let Ok(minutes) = duration.parse() {
-                        pomodoro_lock.set_short_break_duration(minutes);
-                    }
-                }
-                ["long", duration] => {
-                    if let Ok(minutes) = duration.parse() {
-                        pomodoro_lock.set_long_break_duration(minutes);
-                    }
-                }
-                ["interval", count] => {
-                    if let Ok(interval) = count.parse() {
-                        pomodoro_lock.set_long_break_interval(interval);
-                    }
-                }
-                ["pause"] => paused = true,
-                ["resume"] => paused = false,
-                _ => println!("未知命令: {command}"),
-            }
-            drop(pomodoro_lock);
-        }
-
-        if paused {
-            tokio::time::sleep(Duration::from_millis(100)).await;
-            continue;
-        }
-
-        let mut target_datetimes: Vec<(String, NaiveDateTime)> = config
-            .get_config()
-            .await
-            .countdown
-            .into_iter()
-            .filter(|countdown| countdown.enabled)
-            .filter_map(|countdown| {
-                match NaiveDateTime::parse_from_str(&countdown.datetime, "%Y-%m-%d %H:%M:%S") {
-                    Ok(datetime) => Some((countdown.title.clone(), datetime)),
-                    Err(_) => {
-                        println!(
-                            "错误：'{}' 的日期时间格式无效。请使用 'YYYY-MM-DD HH:MM:SS' 格式。",
-                            countdown.title
-                        );
-                        None
-                    }
-                }
-            })
-            .collect();
-
-        target_datetimes.sort_by(|a, b| a.1.cmp(&b.1));
-
-        // 清除之前的输出
-        if !clean_without_output {
-            for _ in 0..last_line_count {
-                let _ = stdout.execute(cursor::MoveUp(1));
-                let _ = stdout.execute(Clear(ClearType::CurrentLine));
-            }
+        let now = Utc::now();
+        scheduler.update(now).await;
+
+        let mut pomodoro_lock = pomodoro.lock().await;
+        pomodoro_lock.set_cycle(cli_args, &scheduler.pomodoro);
+        if pomodoro_lock.state != PomodoroState::Idle
+            && pomodoro_lock.remaining_time() == Some(Duration::from_secs(0))
+        {
+            pomodoro_lock.next_state();
+            scheduler
+                .notifiers
+                .send(i18n::t("pomodoro.phase_end_notify"), "");
+            let next = match pomodoro_lock.state {
+                PomodoroState::Idle => i18n::t("pomodoro.prompt_next").to_string(),
+                state => i18n::tf("pomodoro.auto_next", &[("phase", &state.label())]),
+            };
+            dashboard.notice(i18n::tf("pomodoro.phase_end", &[("next", &next)]));
         }
 
-        let _ = stdout.execute(cursor::MoveToColumn(0));
-
-        let mut current_line_count = 0;
-
-        // 显示番茄钟状态
-        let pomodoro_lock = pomodoro.lock().await;
-        match pomodoro_lock.state {
-            PomodoroState::Idle => {
-                if let Some(time_since_completion) = pomodoro_lock.time_since_last_completion() {
-                    println!(
-                        "番茄钟未启动，上次完成后已经过去: {:02}:{:02}",
-                        time_since_completion.as_secs() / 60,
-                        time_since_completion.as_secs() % 60
-                    );
-                    current_line_count += 1;
-                } else {
-                    println!("番茄钟未启动");
-                    current_line_count += 1;
-                }
-            }
-            _ => {
-                if let Some(remaining) = pomodoro_lock.remaining_time() {
-                    println!(
-                        "番茄钟状态: {:?}, 剩余时间: {:02}:{:02}",
-                        pomodoro_lock.state,
-                        remaining.as_secs() / 60,
-                        remaining.as_secs() % 60
-                    );
-                    current_line_count += 1;
-                    if remaining.as_secs() == 0 {
-                        println!("当前阶段结束！");
-                        drop(pomodoro_lock);
-                        pomodoro.lock().await.next_state();
-                        let _ = osx_terminal_notifier(
-                            "番茄钟：当前阶段结束！",
-                            "",
-                            notify_sound.clone(),
-                        )
-                        .await;
-                        let pomodoro_lock = pomodoro.lock().await;
-                        println!(
-                            "已完成的工作周期: {}",
-                            pomodoro_lock.completed_work_sessions
-                        );
-                        current_line_count += 1;
-                        println!("请输入下一个命令（start/short/long）来开始新的阶段");
-                        current_line_count += 1;
-                        clean_without_output = true;
-                        continue;
-                    }
-                }
-            }
-        }
-        println!(
-            "已完成的工作周期: {}",
-            pomodoro_lock.completed_work_sessions
+        let (width, height) = Terminal::size();
+        let (frame, cursor_at) = dashboard.render(
+            width,
+            height,
+            &pomodoro_lock,
+            &scheduler.scheduled,
+            &scheduler.errors,
+            now,
         );
-        current_line_count += 1;
+        let wait = scheduler.wait_time(now, dashboard.next_frame(&pomodoro_lock, now));
         drop(pomodoro_lock);
-
-        for (title, target_datetime) in target_datetimes.iter() {
-            let now = Local::now().naive_local();
-            let remaining = *target_datetime - now;
-            let remaining_seconds = remaining.num_seconds();
-
-            let message = match remaining_seconds {
-                86401_i64..=i64::MAX => {
-                    let days_left = remaining.num_days();
-                    let hours_left = format!("{:02}", remaining.num_hours() % 24);
-                    let minutes_left = format!("{:02}", remaining.num_minutes() % 60);
-                    let secs_left = format!("{:02}", remaining.num_seconds() % 60);
-                    format!(
-                        "{}: There are {} days, {:02}:{:02}:{:02} secs left.",
-                        title.bright_magenta(),
-                        days_left.to_string().bright_yellow(),
-                        hours_left.bright_yellow(),
-                        minutes_left.bright_yellow(),
-                        secs_left.bright_yellow()
-                    )
-                }
-                1_i64..=86400 => {
-                    let hours_left = format!("{:02}", remaining.num_hours() % 24);
-                    let minutes_left = format!("{:02}", remaining.num_minutes() % 60);
-                    let secs_left = format!("{:02}", remaining.num_seconds() % 60);
-                    let mills_left = format!("{:03}", remaining.num_milliseconds() % 1000);
-                    format!(
-                        "{}: There are {:02}:{:02}:{:02}.{:03} secs left.",
-                        title.bright_red(),
-                        hours_left.bright_yellow(),
-                        minutes_left.bright_yellow(),
-                        secs_left.bright_yellow(),
-                        mills_left.bright_yellow()
-                    )
-                }
-                0 => {
-                    /*  TODO: notify how many time need be controlled precision,not like this fixed sleep.
-                    need fix it later.
-                    not play any sound for now.*/
-                    let _ = osx_terminal_notifier(title, "", notify_sound.clone()).await;
-                    sleep(StdDuration::from_millis(500)).await;
-                    format!("{title}: Now is the time!")
-                }
-                i64::MIN..=-1_i64 => {
-                    format!(
-                        "{}: The datetime was {} seconds ago.",
-                        title, -remaining_seconds
-                    )
+        let _ = terminal.draw(frame, cursor_at);
+
+        // 睡到画面需要更新、到点、配置变化或有按键为止
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = scheduler.config_changed() => {}
+            Some(event) = rx.recv() => {
+                let mut next = Some(event);
+                while let Some(event) = next {
+                    match event {
+                        Event::Key(key) => match dashboard.handle_key(key) {
+                            Action::None => {}
+                            Action::Quit => if_running.store(false, Ordering::SeqCst),
+                            Action::Command(command) => {
+                                match pomodoro.lock().await.apply_command(&command) {
+                                    Ok(()) => dashboard
+                                        .notice(i18n::tf("ui.ok", &[("command", &command)])),
+                                    Err(err) => dashboard.error(err),
+                                }
+                            }
+                        },
+                        Event::Resize(_, _) => terminal.invalidate(),
+                        _ => {}
+                    }
+                    next = rx.try_recv().ok();
                 }
-            };
-
-            println!("{message}");
-            current_line_count += 1;
-            stdout.flush().unwrap();
+            }
         }
+    }
 
-        clean_without_output = false;
-        last_line_count = current_line_count;
+    pomodoro.lock().await.save();
+}
 
-        tokio::time::sleep(Duration::from_millis(50)).await;
+// raw 模式下按键和窗口尺寸变化都从这里读取
+// 错误直接显示给用户，不经过 main 返回值的 Debug 格式
+fn exit_on_error(result: Result<(), Box<dyn std::error::Error>>) {
+    if let Err(err) = result {
+        eprintln!("{err}");
+        std::process::exit(1);
     }
 }
 
-fn handle_user_input(tx: std_mpsc::Sender<String>, if_running: Arc<AtomicBool>) {
-    println!("输入 'help' 查看可用命令");
-
+fn handle_terminal_events(tx: tokio_mpsc::UnboundedSender<Event>, if_running: Arc<AtomicBool>) {
     while if_running.load(Ordering::SeqCst) {
-        let mut input = String::new();
-        if std::io::stdin().read_line(&mut input).is_ok() {
-            let input = input.trim().to_string();
-            if input == "help" {
-                tx.send("pause".to_string()).unwrap();
-                print_help();
-                println!("按回车键继续...");
-                let _ = std::io::stdin().read_line(&mut String::new());
-                tx.send("resume".to_string()).unwrap();
-            } else {
-                tx.send(input).unwrap();
+        if !event::poll(scheduler::MAX_SLEEP).unwrap_or(false) {
+            continue;
+        }
+        match event::read() {
+            Ok(event) => {
+                if tx.send(event).is_err() {
+                    break;
+                }
             }
+            Err(_) => break,
         }
     }
 }
 
-fn print_help() {
-    println!("可用命令：");
-    println!("start - 开始工作阶段");
-    println!("short - 开始短休息阶段");
-    println!("long - 开始长休息阶段");
-    println!("stop - 停止番茄钟");
-    println!("next - 手动切换到下一个状态");
-    println!("work <分钟> - 设置工作时间");
-    println!("short <分钟> - 设置短休息时间");
-    println!("long <分钟> - 设置长休息时间");
-    println!("interval <次数> - 设置长休息间隔（工作周期次数）");
-    println!("help - 显示此帮助信息");
-}
-
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli_args = CliArgs::parse();
+    let socket = daemon::socket_path(cli_args.socket.as_deref());
+
+    let config_file = cli_args.config_file.as_str();
+    i18n::set_lang(i18n::select(
+        cli_args.lang,
+        config::configured_lang(config_file),
+    ));
+    let result = match &cli_args.command {
+        None | Some(Commands::Run) => None,
+        Some(Commands::List) => Some(manage::list(config_file)),
+        Some(Commands::Add {
+            title,
+            at,
+            repeat,
+            timezone,
+        }) => Some(manage::add(
+            config_file,
+            title,
+            at,
+            repeat.as_deref(),
+            timezone.as_deref(),
+        )),
+        Some(Commands::Remove { title }) => Some(manage::remove(config_file, title)),
+        Some(Commands::Enable { title }) => Some(manage::set_enabled(config_file, title, true)),
+        Some(Commands::Disable { title }) => Some(manage::set_enabled(config_file, title, false)),
+        Some(Commands::Validate) => Some(manage::validate(config_file).and_then(|problems| {
+            if problems > 0 {
+                Err(i18n::tfn("error.problems", problems as i64, &[("count", &problems)]).into())
+            } else {
+                Ok(())
+            }
+        })),
+        Some(Commands::Stats { export }) => {
+            let records = history::load();
+            match export {
+                Some(format) => Some(history::export(&records, *format)),
+                None => {
+                    history::report(&records);
+                    Some(Ok(()))
+                }
+            }
+        }
+        Some(Commands::Status { format, waybar }) => {
+            Some(output::status_line(config_file, &socket, format, *waybar).await)
+        }
+        Some(Commands::Ctl { args }) => Some(
+            daemon::send_command(&socket, &args.join(" "))
+                .await
+                .and_then(|reply| {
+                    println!("{reply}");
+                    if reply.starts_with("error") {
+                        Err(i18n::t("error.daemon_rejected").into())
+                    } else {
+                        Ok(())
+                    }
+                }),
+        ),
+    };
+    if let Some(result) = result {
+        exit_on_error(result);
+        return Ok(());
+    }
+
     let file_path = cli_args.config_file.clone();
     let notify_sound = cli_args.notify_sound.clone();
 
-    let config = CountDownConfig::try_new(file_path).unwrap();
+    let config = match CountDownConfig::try_new(file_path) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+    };
 
     let running = Arc::new(AtomicBool::new(true));
     let r = running.clone();
-    ctrlc::set_handler(move || {
-        r.store(false, Ordering::SeqCst);
-    })
-    .expect("Error setting Ctrl-C handler");
+    // termination 特性同时处理 SIGTERM 和 SIGHUP，守护进程被 kill 时也能保存状态
+    exit_on_error(
+        ctrlc::set_handler(move || {
+            r.store(false, Ordering::SeqCst);
+        })
+        .map_err(|err| i18n::tf("error.signal_handler", &[("error", &err)]).into()),
+    );
+
+    // 优先监视文件变化，不支持时退回到每秒检查一次；出错原因由 reload 记录
+    if reload::watch(config.clone()).is_err() {
+        let mut config_for_reload = config.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                let _ = config_for_reload.reload().await;
+            }
+        });
+    }
+
+    if let Some(format) = cli_args.output {
+        exit_on_error(output::run(running, config, format, Some(&cli_args)).await);
+        return Ok(());
+    }
+
+    if cli_args.daemon {
+        exit_on_error(
+            daemon::run_daemon(running, config, notify_sound, Some(&cli_args), socket).await,
+        );
+        return Ok(());
+    }
 
     let config_for_spawn = config.clone();
     let if_running_for_spawn = running.clone();
@@ -440,13 +414,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         )
         .await
     });
-    let mut config_for_reload = config.clone();
-    let _reload_handle = tokio::spawn(async move {
-        loop {
-            tokio::time::sleep(Duration::from_secs(1)).await;
-            let _ = config_for_reload.reload().await;
-        }
-    });
 
     countdown_handle.await?;
 
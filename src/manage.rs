@@ -0,0 +1,240 @@
+use chrono::{Local, Utc};
+use toml_edit::{value, ArrayOfTables, DocumentMut, Item, Table};
+
+use crate::config::{self, CountDownData, Countdown, Defaults, Event, FormatConfig, Mode};
+use crate::humanize;
+use crate::i18n;
+
+fn read_document(config_file: &str) -> Result<DocumentMut, Box<dyn std::error::Error>> {
+    let contents = std::fs::read_to_string(config_file).map_err(|err| {
+        i18n::tf(
+            "error.open_file",
+            &[("file", &config_file), ("error", &err)],
+        )
+    })?;
+    Ok(contents.parse::<DocumentMut>()?)
+}
+
+fn write_document(
+    config_file: &str,
+    document: &DocumentMut,
+) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::write(config_file, document.to_string())?;
+    Ok(())
+}
+
+fn countdown_tables(document: &mut DocumentMut) -> Result<&mut ArrayOfTables, String> {
+    document
+        .entry("countdown")
+        .or_insert(Item::ArrayOfTables(ArrayOfTables::new()))
+        .as_array_of_tables_mut()
+        .ok_or_else(|| i18n::t("manage.not_array").to_string())
+}
+
+fn find_countdown(tables: &ArrayOfTables, title: &str) -> Option<usize> {
+    tables
+        .iter()
+        .position(|table| table.get("title").and_then(|item| item.as_str()) == Some(title))
+}
+
+fn line_of(contents: &str, offset: usize) -> usize {
+    config::line_column(contents, offset).0
+}
+
+pub fn list(config_file: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let contents = std::fs::read_to_string(config_file)?;
+    let data: CountDownData = toml::from_str(&contents)?;
+    let now = Utc::now();
+    for countdown in &data.countdown {
+        let mark = if countdown.enabled { "x" } else { " " };
+        let repeat = match (countdown.repeat.as_deref(), countdown.mode) {
+            (Some(rule), _) => format!(" ({rule})"),
+            (None, Mode::Countup) => " (countup)".to_string(),
+            (None, Mode::Countdown) => String::new(),
+        };
+        match Event::new(countdown, &data.defaults, &data.format)
+            .and_then(|event| event.target(now))
+        {
+            Ok(target) => {
+                let relative = humanize::relative(target.at, now);
+                println!(
+                    "[{mark}] {}: {}{repeat}, {relative}",
+                    countdown.title,
+                    target.at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S")
+                );
+            }
+            Err(err) => println!("[{mark}] {}: {err}", countdown.title),
+        }
+    }
+    Ok(())
+}
+
+pub fn add(
+    config_file: &str,
+    title: &str,
+    at: &str,
+    repeat: Option<&str>,
+    timezone: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let datetime = config::normalize_datetime(at, timezone, Utc::now())?;
+    let countdown = Countdown {
+        title: title.to_string(),
+        datetime: datetime.clone(),
+        enabled: true,
+        repeat: repeat.map(str::to_string),
+        timezone: timezone.map(str::to_string),
+        remind: None,
+        start: None,
+        format: None,
+        mode: Mode::Countdown,
+        milestones: Vec::new(),
+    };
+    Event::new(&countdown, &Defaults::default(), &FormatConfig::default())?.target(Utc::now())?;
+
+    let mut document = read_document(config_file)?;
+    let tables = countdown_tables(&mut document)?;
+    if find_countdown(tables, title).is_some() {
+        return Err(i18n::tf("manage.exists", &[("title", &title)]).into());
+    }
+    let mut table = Table::new();
+    table.insert("title", value(title));
+    table.insert("datetime", value(&datetime));
+    if let Some(repeat) = repeat {
+        table.insert("repeat", value(repeat));
+    }
+    if let Some(timezone) = timezone {
+        table.insert("timezone", value(timezone));
+    }
+    tables.push(table);
+    write_document(config_file, &document)?;
+    println!(
+        "{}",
+        i18n::tf(
+            "manage.added",
+            &[("title", &title), ("datetime", &datetime)]
+        )
+    );
+    Ok(())
+}
+
+pub fn remove(config_file: &str, title: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut document = read_document(config_file)?;
+    let tables = countdown_tables(&mut document)?;
+    let index = find_countdown(tables, title)
+        .ok_or_else(|| i18n::tf("manage.not_found", &[("title", &title)]))?;
+    tables.remove(index);
+    write_document(config_file, &document)?;
+    println!("{}", i18n::tf("manage.removed", &[("title", &title)]));
+    Ok(())
+}
+
+pub fn set_enabled(
+    config_file: &str,
+    title: &str,
+    enabled: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut document = read_document(config_file)?;
+    let tables = countdown_tables(&mut document)?;
+    let index = find_countdown(tables, title)
+        .ok_or_else(|| i18n::tf("manage.not_found", &[("title", &title)]))?;
+    let table = tables.get_mut(index).unwrap();
+    if enabled {
+        // enabled 默认为 true，直接去掉该字段
+        table.remove("enabled");
+    } else {
+        table.insert("enabled", value(false));
+    }
+    write_document(config_file, &document)?;
+    let key = if enabled {
+        "manage.enabled"
+    } else {
+        "manage.disabled"
+    };
+    println!("{}", i18n::tf(key, &[("title", &title)]));
+    Ok(())
+}
+
+// 返回发现的问题数量
+pub fn validate(config_file: &str) -> Result<usize, Box<dyn std::error::Error>> {
+    let contents = std::fs::read_to_string(config_file)?;
+    let document = match toml_edit::Document::parse(contents.as_str()) {
+        Ok(document) => document,
+        Err(err) => {
+            let line = err.span().map(|span| line_of(&contents, span.start));
+            println!(
+                "{config_file}:{}: {}",
+                line.map(|line| line.to_string()).unwrap_or_default(),
+                err.message()
+            );
+            return Ok(1);
+        }
+    };
+    let data: CountDownData = match toml::from_str(&contents) {
+        Ok(data) => data,
+        Err(err) => {
+            let line = err.span().map(|span| line_of(&contents, span.start));
+            println!(
+                "{config_file}:{}: {}",
+                line.map(|line| line.to_string()).unwrap_or_default(),
+                err.message()
+            );
+            return Ok(1);
+        }
+    };
+
+    let tables = document
+        .get("countdown")
+        .and_then(|item| item.as_array_of_tables());
+    let now = Utc::now();
+    let mut problems = 0;
+    if let Err(err) = data.format.check("[format]") {
+        let line = document
+            .get("format")
+            .and_then(|item| item.span())
+            .map(|span| line_of(&contents, span.start).to_string())
+            .unwrap_or_default();
+        println!("{config_file}:{line}: {err}");
+        problems += 1;
+    }
+    for err in data.notify.check() {
+        let line = document
+            .get("notify")
+            .and_then(|item| item.get("sinks"))
+            .and_then(|item| item.span())
+            .map(|span| line_of(&contents, span.start).to_string())
+            .unwrap_or_default();
+        println!("{config_file}:{line}: {err}");
+        problems += 1;
+    }
+    for (index, countdown) in data.countdown.iter().enumerate() {
+        let Err(err) =
+            Event::new(countdown, &data.defaults, &data.format).and_then(|event| event.target(now))
+        else {
+            continue;
+        };
+        let line = tables
+            .and_then(|tables| tables.get(index))
+            .and_then(|table| {
+                table
+                    .get("datetime")
+                    .and_then(|item| item.span())
+                    .or(table.span())
+            })
+            .map(|span| line_of(&contents, span.start).to_string())
+            .unwrap_or_default();
+        println!("{config_file}:{line}: {err}");
+        problems += 1;
+    }
+    if problems == 0 {
+        let count = data.countdown.len() as i64;
+        println!(
+            "{}",
+            i18n::tfn(
+                "manage.ok",
+                count,
+                &[("file", &config_file), ("count", &count)]
+            )
+        );
+    }
+    Ok(problems)
+}
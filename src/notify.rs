@@ -1,22 +1,272 @@
+use std::io::Write;
+use std::process::Child;
 use std::process::Stdio;
+use std::sync::{Arc, Mutex};
 
-fn check_path_exist(path: &str) -> bool {
-    let path_obj = std::path::Path::new(path);
-    if path_obj.exists() {
-        true
+use chrono::Local;
+
+use crate::config::NotifyConfig;
+use crate::i18n;
+
+pub trait Notifier: Send + Sync {
+    fn notify(&self, title: &str, content: &str) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+#[derive(Clone, Default)]
+pub struct Notifiers {
+    sinks: Vec<(String, Arc<dyn Notifier>)>,
+    // 最近一次发送失败的原因，显示在诊断面板里
+    failure: Arc<Mutex<Option<String>>>,
+}
+
+impl Notifiers {
+    pub fn from_config(config: &NotifyConfig, notify_sound: Option<String>) -> Self {
+        let notify_sound = notify_sound.filter(|sound| !sound.is_empty());
+        let mut sinks: Vec<(String, Arc<dyn Notifier>)> = Vec::new();
+        let mut failure = None;
+        for sink in &config.sinks {
+            // 未知通道和缺少的设置在加载配置时已经报告过
+            let notifier: Arc<dyn Notifier> = match sink.as_str() {
+                "desktop" => Arc::new(DesktopNotifier {
+                    sound: notify_sound.clone(),
+                }),
+                "bell" => Arc::new(BellNotifier),
+                "sound" => match config.sound.clone().or(notify_sound.clone()) {
+                    Some(path) => Arc::new(SoundNotifier { path }),
+                    None => {
+                        failure = Some(i18n::tf(
+                            "config.missing_sink_setting",
+                            &[("sink", sink), ("setting", &"sound")],
+                        ));
+                        continue;
+                    }
+                },
+                "command" => match config.command.clone() {
+                    Some(command) => Arc::new(CommandNotifier { command }),
+                    None => continue,
+                },
+                "webhook" => match config.webhook.clone() {
+                    Some(url) => Arc::new(WebhookNotifier { url }),
+                    None => continue,
+                },
+                "log" => match config.log_file.clone() {
+                    Some(path) => Arc::new(LogFileNotifier { path }),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            sinks.push((sink.clone(), notifier));
+        }
+        Notifiers {
+            sinks,
+            failure: Arc::new(Mutex::new(failure)),
+        }
+    }
+
+    // 每个通道在独立的阻塞线程中发送，不阻塞渲染循环
+    pub fn send(&self, title: &str, content: &str) {
+        for (name, sink) in &self.sinks {
+            let name = name.clone();
+            let sink = sink.clone();
+            let failure = self.failure.clone();
+            let title = title.to_string();
+            let content = content.to_string();
+            tokio::task::spawn_blocking(move || {
+                if let Err(err) = sink.notify(&title, &content) {
+                    let message = i18n::tf("notify.failed", &[("sink", &name), ("error", &err)]);
+                    if let Ok(mut failure) = failure.lock() {
+                        *failure = Some(message);
+                    }
+                }
+            });
+        }
+    }
+
+    pub fn failure(&self) -> Option<String> {
+        self.failure.lock().ok()?.clone()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NotifyBackend {
+    TerminalNotifier,
+    Freedesktop,
+    NotifySend,
+    Unavailable,
+}
+
+// 在发送线程里调用，不能直接打印到正在显示界面的终端
+fn check_path_exist(path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if std::path::Path::new(path).exists() {
+        Ok(())
     } else {
-        println!("{path} not exist!");
-        false
+        Err(i18n::tf("notify.missing_sound", &[("path", &path)]).into())
+    }
+}
+
+// 等待子进程结束，退出状态不为 0 时返回错误
+fn wait_success(mut process: Child, command: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let status = process.wait()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(i18n::tf(
+            "notify.exit_status",
+            &[("command", &command), ("status", &status)],
+        )
+        .into())
+    }
+}
+
+fn command_in_path(command: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(command).is_file()))
+        .unwrap_or(false)
+}
+
+pub fn detect_backend() -> NotifyBackend {
+    if cfg!(target_os = "macos") && command_in_path("terminal-notifier") {
+        NotifyBackend::TerminalNotifier
+    } else if cfg!(target_os = "linux") && std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_some() {
+        NotifyBackend::Freedesktop
+    } else if command_in_path("notify-send") {
+        NotifyBackend::NotifySend
+    } else if command_in_path("terminal-notifier") {
+        NotifyBackend::TerminalNotifier
+    } else {
+        NotifyBackend::Unavailable
+    }
+}
+
+fn play_sound(sound: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(sound_path) = sound {
+        check_path_exist(&sound_path)?;
+        let mut sound_process = std::process::Command::new("ffplay")
+            .args(["-i", &sound_path, "-autoexit", "-nodisp"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let _ = sound_process.wait();
+    }
+    Ok(())
+}
+
+pub struct DesktopNotifier {
+    sound: Option<String>,
+}
+
+impl Notifier for DesktopNotifier {
+    fn notify(&self, title: &str, content: &str) -> Result<(), Box<dyn std::error::Error>> {
+        match detect_backend() {
+            NotifyBackend::TerminalNotifier => {
+                osx_terminal_notifier(title, content, self.sound.clone())
+            }
+            NotifyBackend::Freedesktop => {
+                // D-Bus 不可用时退回 notify-send
+                if linux_freedesktop_notifier(title, content).is_err() {
+                    notify_send_notifier(title, content)?;
+                }
+                play_sound(self.sound.clone())
+            }
+            NotifyBackend::NotifySend => {
+                notify_send_notifier(title, content)?;
+                play_sound(self.sound.clone())
+            }
+            NotifyBackend::Unavailable => Err(i18n::t("notify.unavailable").into()),
+        }
+    }
+}
+
+pub struct BellNotifier;
+
+impl Notifier for BellNotifier {
+    fn notify(&self, _title: &str, _content: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let mut stdout = std::io::stdout();
+        stdout.write_all(b"\x07")?;
+        stdout.flush()?;
+        Ok(())
     }
 }
 
-pub async fn osx_terminal_notifier(
+pub struct SoundNotifier {
+    path: String,
+}
+
+impl Notifier for SoundNotifier {
+    fn notify(&self, _title: &str, _content: &str) -> Result<(), Box<dyn std::error::Error>> {
+        play_sound(Some(self.path.clone()))
+    }
+}
+
+pub struct CommandNotifier {
+    command: String,
+}
+
+impl Notifier for CommandNotifier {
+    fn notify(&self, title: &str, content: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let process = std::process::Command::new("sh")
+            .args(["-c", &self.command])
+            .env("COUNTDOWN_TITLE", title)
+            .env("COUNTDOWN_CONTENT", content)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        wait_success(process, &self.command)
+    }
+}
+
+pub struct WebhookNotifier {
+    url: String,
+}
+
+impl Notifier for WebhookNotifier {
+    fn notify(&self, title: &str, content: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let payload = serde_json::json!({
+            "title": title,
+            "content": content,
+            "time": Local::now().to_rfc3339(),
+        });
+        // -f 让 curl 在 HTTP 错误时也返回非 0
+        let process = std::process::Command::new("curl")
+            .args(["-sSf", "-X", "POST", "-H", "Content-Type: application/json"])
+            .args(["-d", &payload.to_string(), &self.url])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        wait_success(process, "curl")
+    }
+}
+
+pub struct LogFileNotifier {
+    path: String,
+}
+
+impl Notifier for LogFileNotifier {
+    fn notify(&self, title: &str, content: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(
+            file,
+            "{}\t{title}\t{content}",
+            Local::now().format("%Y-%m-%d %H:%M:%S")
+        )?;
+        Ok(())
+    }
+}
+
+pub fn osx_terminal_notifier(
     title: &str,
     content: &str,
     sound: Option<String>,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let mut missing = None;
     if let Some(sound_path) = sound {
-        if check_path_exist(&sound_path) {
+        if let Err(err) = check_path_exist(&sound_path) {
+            // 声音文件不存在时改用默认提示音，通知发出后再报告
+            missing = Some(err);
+        } else {
             let mut notify_window = std::process::Command::new("terminal-notifier")
                 .args(["-message", content, "-title", title])
                 .spawn()?;
@@ -32,8 +282,37 @@ pub async fn osx_terminal_notifier(
     }
     let mut notify_window = std::process::Command::new("terminal-notifier")
         .args(["-message", content, "-title", title, "-sound", "default"])
-        .spawn()
-        .unwrap();
+        .spawn()?;
     let _ = notify_window.wait();
+    missing.map_or(Ok(()), Err)
+}
+
+#[cfg(target_os = "linux")]
+fn linux_freedesktop_notifier(
+    title: &str,
+    content: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    notify_rust::Notification::new()
+        .appname("countdown_cli")
+        .summary(title)
+        .body(content)
+        .show()?;
     Ok(())
 }
+
+#[cfg(not(target_os = "linux"))]
+fn linux_freedesktop_notifier(
+    _title: &str,
+    _content: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    Err(i18n::t("notify.linux_only").into())
+}
+
+fn notify_send_notifier(title: &str, content: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let notify_window = std::process::Command::new("notify-send")
+        .args(["--app-name=countdown_cli", title, content])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    wait_success(notify_window, "notify-send")
+}
@@ -0,0 +1,267 @@
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::config::{CountDownConfig, CountDownData, Mode};
+use crate::daemon;
+use crate::engine::{self, Scheduled};
+use crate::i18n;
+use crate::pomodoro::{PomodoroState, PomodoroTimer};
+use crate::scheduler;
+use crate::CliArgs;
+
+pub const DEFAULT_STATUS_FORMAT: &str =
+    "{pomodoro_state} {pomodoro_remaining} | {next_title} {next_remaining}";
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum OutputFormat {
+    Json,
+    Ndjson,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Future,
+    Now,
+    Past,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct PomodoroStatus {
+    pub state: PomodoroState,
+    pub remaining_secs: Option<u64>,
+    pub paused: bool,
+    pub completed_work_sessions: u32,
+    #[serde(default)]
+    pub task: Option<String>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct CountdownStatus {
+    pub title: String,
+    pub target: String,
+    pub timezone: String,
+    pub remaining_secs: i64,
+    pub status: Status,
+    pub mode: Mode,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Snapshot {
+    pub pomodoro: PomodoroStatus,
+    pub countdowns: Vec<CountdownStatus>,
+    pub errors: Vec<String>,
+}
+
+pub fn pomodoro_status(pomodoro: &PomodoroTimer) -> PomodoroStatus {
+    PomodoroStatus {
+        state: pomodoro.state,
+        remaining_secs: pomodoro
+            .remaining_time()
+            .map(|remaining| remaining.as_secs()),
+        paused: pomodoro.is_paused(),
+        completed_work_sessions: pomodoro.completed_work_sessions,
+        task: pomodoro.task.clone(),
+    }
+}
+
+pub fn countdown_status(entry: &Scheduled, now: DateTime<Utc>) -> CountdownStatus {
+    let remaining_secs = (entry.target.at - now).num_seconds();
+    CountdownStatus {
+        title: entry.title.clone(),
+        target: entry.target.at.to_rfc3339_opts(SecondsFormat::Secs, true),
+        timezone: entry.target.zone.name(),
+        remaining_secs,
+        status: match remaining_secs {
+            1.. => Status::Future,
+            0 => Status::Now,
+            _ => Status::Past,
+        },
+        mode: entry.mode,
+    }
+}
+
+pub fn snapshot(
+    pomodoro: PomodoroStatus,
+    scheduled: &[Scheduled],
+    errors: Vec<String>,
+    now: DateTime<Utc>,
+) -> Snapshot {
+    Snapshot {
+        pomodoro,
+        countdowns: scheduled
+            .iter()
+            .map(|entry| countdown_status(entry, now))
+            .collect(),
+        errors,
+    }
+}
+
+// 只读输出，不触发通知，番茄钟状态和 status 子命令一样先问守护进程
+pub async fn run(
+    if_running: Arc<AtomicBool>,
+    config: CountDownConfig,
+    format: OutputFormat,
+    cli_args: Option<&CliArgs>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let socket = daemon::socket_path(cli_args.and_then(|args| args.socket.as_deref()));
+    let mut stdout = std::io::stdout();
+    let mut last: Option<Snapshot> = None;
+    while if_running.load(Ordering::SeqCst) {
+        let now = Utc::now();
+        let config_data = config.get_config().await;
+        i18n::set_lang(i18n::select(
+            cli_args.and_then(|args| args.lang),
+            config_data.lang,
+        ));
+        let errors = config
+            .reload_error()
+            .await
+            .map(|err| err.lines())
+            .unwrap_or_default();
+        let scheduled = engine::schedule(config_data, now);
+        let pomodoro = current_pomodoro(&socket, cli_args).await;
+        let current = snapshot(pomodoro, &scheduled, errors, now);
+
+        let line = match format {
+            OutputFormat::Json => Some(serde_json::to_string_pretty(&current)?),
+            // 内容变化时才输出一行，剩余秒数每秒都会变
+            OutputFormat::Ndjson if last.as_ref() != Some(&current) => {
+                Some(serde_json::to_string(&current)?)
+            }
+            OutputFormat::Ndjson => None,
+        };
+        if let Some(line) = line {
+            match writeln!(stdout, "{line}").and_then(|_| stdout.flush()) {
+                Ok(()) => {}
+                // 下游管道关闭（例如 | head）时正常退出
+                Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => return Ok(()),
+                Err(err) => return Err(err.into()),
+            }
+            if format == OutputFormat::Json {
+                return Ok(());
+            }
+            last = Some(current);
+        }
+
+        // 剩余秒数在整秒处变化
+        tokio::time::sleep(scheduler::until_next_second(Utc::now())).await;
+    }
+    Ok(())
+}
+
+// 紧凑的剩余时间：超过一天带天数
+fn compact_remaining(secs: i64) -> String {
+    let secs = secs.max(0);
+    let clock = format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    );
+    match secs / 86400 {
+        0 => clock,
+        days => format!("{days}d {clock}"),
+    }
+}
+
+// 番茄钟状态优先从守护进程读取，没有运行时读状态文件
+async fn current_pomodoro(socket: &Path, cli_args: Option<&CliArgs>) -> PomodoroStatus {
+    if let Ok(reply) = daemon::send_command(socket, "status").await {
+        if let Ok(status) = serde_json::from_str(&reply) {
+            return status;
+        }
+    }
+    let mut status = pomodoro_status(&PomodoroTimer::load(cli_args));
+    // 没有进程推进阶段时，状态文件里的阶段可能早已结束
+    if status.state != PomodoroState::Idle && status.remaining_secs == Some(0) {
+        status.state = PomodoroState::Idle;
+        status.remaining_secs = None;
+        status.paused = false;
+    }
+    status
+}
+
+pub async fn status_line(
+    config_file: &str,
+    socket: &Path,
+    template: &str,
+    waybar: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let contents = std::fs::read_to_string(config_file)?;
+    // 状态栏里一个条目写错不影响显示其它倒计时
+    let data = CountDownData::load_valid(config_file, &contents)?;
+    let now = Utc::now();
+    let scheduled = engine::schedule(data, now);
+    let upcoming: Vec<CountdownStatus> = scheduled
+        .iter()
+        .map(|entry| countdown_status(entry, now))
+        .filter(|status| status.status != Status::Past)
+        .collect();
+    let next = upcoming.first();
+    let pomodoro = current_pomodoro(socket, None).await;
+
+    let remaining = pomodoro
+        .remaining_secs
+        .filter(|_| pomodoro.state != PomodoroState::Idle)
+        .map(|secs| format!("{:02}:{:02}", secs / 60, secs % 60))
+        .unwrap_or_else(|| "--:--".to_string());
+    let next_remaining = next
+        .map(|next| compact_remaining(next.remaining_secs))
+        .unwrap_or_default();
+    let line = i18n::fill(
+        template,
+        &[
+            ("pomodoro_state", &pomodoro.state.label()),
+            ("pomodoro_remaining", &remaining),
+            (
+                "pomodoro_task",
+                &pomodoro.task.as_deref().unwrap_or_default(),
+            ),
+            (
+                "next_title",
+                &next.map(|next| next.title.as_str()).unwrap_or_default(),
+            ),
+            ("next_remaining", &next_remaining),
+            (
+                "next_target",
+                &next.map(|next| next.target.as_str()).unwrap_or_default(),
+            ),
+        ],
+    );
+    let line = line.trim();
+
+    if !waybar {
+        println!("{line}");
+        return Ok(());
+    }
+    let tooltip: Vec<String> = upcoming
+        .iter()
+        .map(|status| {
+            format!(
+                "{}: {}",
+                status.title,
+                compact_remaining(status.remaining_secs)
+            )
+        })
+        .collect();
+    let class = if pomodoro.paused {
+        "paused".to_string()
+    } else {
+        format!("{:?}", pomodoro.state).to_lowercase()
+    };
+    println!(
+        "{}",
+        serde_json::json!({
+            "text": line,
+            "tooltip": tooltip.join("\n"),
+            "class": class,
+        })
+    );
+    Ok(())
+}
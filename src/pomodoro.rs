@@ -0,0 +1,522 @@
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::config::PomodoroConfig;
+use crate::history::{self, PhaseRecord};
+use crate::i18n;
+use crate::state;
+use crate::CliArgs;
+
+const POMODORO_FILE: &str = "pomodoro.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PomodoroState {
+    Idle,
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl PomodoroState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PomodoroState::Idle => i18n::t("phase.Idle"),
+            PomodoroState::Work => i18n::t("phase.Work"),
+            PomodoroState::ShortBreak => i18n::t("phase.ShortBreak"),
+            PomodoroState::LongBreak => i18n::t("phase.LongBreak"),
+        }
+    }
+}
+
+pub struct PomodoroTimer {
+    start_time: Option<Instant>,
+    work_duration: Duration,
+    short_break_duration: Duration,
+    long_break_duration: Duration,
+    pub state: PomodoroState,
+    pub completed_work_sessions: u32,
+    long_break_interval: u32,
+    last_completed_time: Option<Instant>,
+    pub task: Option<String>,
+    paused_at: Option<Instant>,
+    paused_total: Duration,
+    pause_count: u32,
+    auto_advance: bool,
+    confirm_work: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PomodoroSnapshot {
+    state: PomodoroState,
+    started_at: Option<DateTime<Utc>>,
+    work_secs: u64,
+    short_break_secs: u64,
+    long_break_secs: u64,
+    completed_work_sessions: u32,
+    long_break_interval: u32,
+    last_completed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    task: Option<String>,
+    #[serde(default)]
+    paused_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    paused_secs: u64,
+    #[serde(default)]
+    pause_count: u32,
+}
+
+// Instant 无法持久化，换算成墙上时间保存
+fn instant_to_wall(instant: Instant) -> DateTime<Utc> {
+    Utc::now() - chrono::Duration::from_std(instant.elapsed()).unwrap_or_default()
+}
+
+fn wall_to_instant(datetime: DateTime<Utc>) -> Instant {
+    let elapsed = (Utc::now() - datetime).to_std().unwrap_or_default();
+    Instant::now()
+        .checked_sub(elapsed)
+        .unwrap_or_else(Instant::now)
+}
+
+impl PomodoroTimer {
+    // 从状态文件恢复，命令行显式指定的时长优先
+    pub fn load(cli_args: Option<&CliArgs>) -> Self {
+        let mut timer = Self::new(cli_args);
+        let Some(snapshot) = state::load_json::<PomodoroSnapshot>(POMODORO_FILE) else {
+            return timer;
+        };
+        timer.state = snapshot.state;
+        timer.start_time = snapshot.started_at.map(wall_to_instant);
+        timer.completed_work_sessions = snapshot.completed_work_sessions;
+        timer.last_completed_time = snapshot.last_completed_at.map(wall_to_instant);
+        timer.task = snapshot.task;
+        timer.paused_at = snapshot.paused_at.map(wall_to_instant);
+        timer.paused_total = Duration::from_secs(snapshot.paused_secs);
+        timer.pause_count = snapshot.pause_count;
+        if cli_args.and_then(|args| args.work_duration).is_none() {
+            timer.work_duration = Duration::from_secs(snapshot.work_secs);
+        }
+        if cli_args
+            .and_then(|args| args.short_break_duration)
+            .is_none()
+        {
+            timer.short_break_duration = Duration::from_secs(snapshot.short_break_secs);
+        }
+        if cli_args.and_then(|args| args.long_break_duration).is_none() {
+            timer.long_break_duration = Duration::from_secs(snapshot.long_break_secs);
+        }
+        if cli_args.and_then(|args| args.long_break_interval).is_none() {
+            timer.long_break_interval = snapshot.long_break_interval;
+        }
+        timer
+    }
+
+    pub fn save(&self) {
+        let snapshot = PomodoroSnapshot {
+            state: self.state,
+            started_at: self.start_time.map(instant_to_wall),
+            work_secs: self.work_duration.as_secs(),
+            short_break_secs: self.short_break_duration.as_secs(),
+            long_break_secs: self.long_break_duration.as_secs(),
+            completed_work_sessions: self.completed_work_sessions,
+            long_break_interval: self.long_break_interval,
+            last_completed_at: self.last_completed_time.map(instant_to_wall),
+            task: self.task.clone(),
+            paused_at: self.paused_at.map(instant_to_wall),
+            paused_secs: self.paused_total.as_secs(),
+            pause_count: self.pause_count,
+        };
+        let _ = state::save_json(POMODORO_FILE, &snapshot);
+    }
+
+    pub fn new(cli_args: Option<&CliArgs>) -> Self {
+        // 使用命令行参数，如果没有则使用默认值
+        let work_duration = cli_args.and_then(|args| args.work_duration).unwrap_or(25);
+        let short_break_duration = cli_args
+            .and_then(|args| args.short_break_duration)
+            .unwrap_or(5);
+        let long_break_duration = cli_args
+            .and_then(|args| args.long_break_duration)
+            .unwrap_or(15);
+        let long_break_interval = cli_args
+            .and_then(|args| args.long_break_interval)
+            .unwrap_or(4);
+
+        PomodoroTimer {
+            start_time: None,
+            work_duration: Duration::from_secs(work_duration * 60),
+            short_break_duration: Duration::from_secs(short_break_duration * 60),
+            long_break_duration: Duration::from_secs(long_break_duration * 60),
+            state: PomodoroState::Idle,
+            completed_work_sessions: 0,
+            long_break_interval,
+            last_completed_time: None,
+            task: None,
+            paused_at: None,
+            paused_total: Duration::ZERO,
+            pause_count: 0,
+            auto_advance: cli_args.is_some_and(|args| args.auto_advance),
+            confirm_work: cli_args.is_some_and(|args| args.confirm_work),
+        }
+    }
+
+    // 把当前阶段（完成或中断）写入历史记录
+    fn record_phase(&self) {
+        if let Some(record) = self.phase_record() {
+            let _ = history::append(&record);
+        }
+    }
+
+    fn phase_record(&self) -> Option<PhaseRecord> {
+        let (Some(start), false) = (self.start_time, self.state == PomodoroState::Idle) else {
+            return None;
+        };
+        let planned = self.phase_duration();
+        let actual = self.elapsed().unwrap_or_default().min(planned);
+        Some(PhaseRecord {
+            start: instant_to_wall(start),
+            end: Utc::now(),
+            phase: self.state,
+            planned_secs: planned.as_secs(),
+            actual_secs: actual.as_secs(),
+            completed: actual >= planned,
+            task: self
+                .task
+                .clone()
+                .filter(|_| self.state == PomodoroState::Work),
+            pause_count: self.pause_count,
+            paused_secs: self.paused_duration().as_secs(),
+        })
+    }
+
+    fn paused_duration(&self) -> Duration {
+        self.paused_total + self.paused_at.map(|at| at.elapsed()).unwrap_or_default()
+    }
+
+    // 当前阶段实际计时的时间，不含暂停
+    fn elapsed(&self) -> Option<Duration> {
+        self.start_time
+            .map(|start| start.elapsed().saturating_sub(self.paused_duration()))
+    }
+
+    fn reset_pause(&mut self) {
+        self.paused_at = None;
+        self.paused_total = Duration::ZERO;
+        self.pause_count = 0;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    pub fn pause(&mut self) -> Result<(), String> {
+        if self.start_time.is_none() || self.state == PomodoroState::Idle {
+            return Err(i18n::t("pomodoro.not_started").to_string());
+        }
+        if self.paused_at.is_none() {
+            self.paused_at = Some(Instant::now());
+            self.pause_count += 1;
+        }
+        Ok(())
+    }
+
+    pub fn resume(&mut self) {
+        if let Some(at) = self.paused_at.take() {
+            self.paused_total += at.elapsed();
+        }
+    }
+
+    fn phase_duration(&self) -> Duration {
+        match self.state {
+            PomodoroState::Work => self.work_duration,
+            PomodoroState::ShortBreak => self.short_break_duration,
+            PomodoroState::LongBreak => self.long_break_duration,
+            PomodoroState::Idle => Duration::from_secs(0),
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.record_phase();
+        self.reset_pause();
+        self.start_time = None;
+        self.state = PomodoroState::Idle;
+    }
+
+    pub fn remaining_time(&self) -> Option<Duration> {
+        self.elapsed().map(|elapsed| {
+            let duration = self.phase_duration();
+            if elapsed >= duration {
+                Duration::from_secs(0)
+            } else {
+                duration - elapsed
+            }
+        })
+    }
+
+    // 命令行开关或配置文件任一开启即生效，配置热加载后随之更新
+    pub fn set_cycle(&mut self, cli_args: Option<&CliArgs>, config: &PomodoroConfig) {
+        self.auto_advance = cli_args.is_some_and(|args| args.auto_advance) || config.auto_advance;
+        self.confirm_work = cli_args.is_some_and(|args| args.confirm_work) || config.confirm_work;
+    }
+
+    // 工作结束后按长休息间隔决定短休息还是长休息，休息结束后回到工作
+    fn following_state(&self) -> PomodoroState {
+        match self.state {
+            PomodoroState::Work
+                if self
+                    .completed_work_sessions
+                    .is_multiple_of(self.long_break_interval) =>
+            {
+                PomodoroState::LongBreak
+            }
+            PomodoroState::Work => PomodoroState::ShortBreak,
+            PomodoroState::ShortBreak | PomodoroState::LongBreak => PomodoroState::Work,
+            PomodoroState::Idle => PomodoroState::Idle,
+        }
+    }
+
+    // 当前阶段已完成的比例
+    pub fn progress(&self) -> Option<f64> {
+        let duration = self.phase_duration();
+        if self.state == PomodoroState::Idle || duration.is_zero() {
+            return None;
+        }
+        self.elapsed()
+            .map(|elapsed| (elapsed.as_secs_f64() / duration.as_secs_f64()).min(1.0))
+    }
+
+    pub fn next_state(&mut self) {
+        self.record_phase();
+        match self.state {
+            PomodoroState::Work => {
+                self.completed_work_sessions += 1;
+                self.last_completed_time = Some(Instant::now());
+            }
+            PomodoroState::ShortBreak | PomodoroState::LongBreak => {
+                self.last_completed_time = Some(Instant::now());
+            }
+            PomodoroState::Idle => {}
+        }
+        let following = self.following_state();
+        self.reset_pause();
+        if self.auto_advance
+            && following != PomodoroState::Idle
+            && !(following == PomodoroState::Work && self.confirm_work)
+        {
+            self.state = following;
+            self.start_time = Some(Instant::now());
+        } else {
+            self.state = PomodoroState::Idle;
+            self.start_time = None;
+        }
+        self.save();
+    }
+
+    pub fn set_state(&mut self, new_state: PomodoroState) {
+        self.record_phase();
+        self.reset_pause();
+        self.state = new_state;
+        self.start_time = Some(Instant::now());
+        self.last_completed_time = None; // 清除上次完成时间
+    }
+
+    pub fn set_work_duration(&mut self, duration: Duration) {
+        self.work_duration = duration;
+    }
+
+    pub fn set_short_break_duration(&mut self, duration: Duration) {
+        self.short_break_duration = duration;
+    }
+
+    pub fn set_long_break_duration(&mut self, duration: Duration) {
+        self.long_break_duration = duration;
+    }
+
+    pub fn set_long_break_interval(&mut self, interval: u32) {
+        self.long_break_interval = interval;
+    }
+
+    pub fn time_since_last_completion(&self) -> Option<Duration> {
+        self.last_completed_time.map(|time| time.elapsed())
+    }
+
+    pub fn apply_command(&mut self, command: &str) -> Result<(), String> {
+        match command.split_whitespace().collect::<Vec<_>>().as_slice() {
+            ["start"] => {
+                self.task = None;
+                self.set_state(PomodoroState::Work);
+            }
+            ["start", task @ ..] => {
+                self.task = Some(task.join(" "));
+                self.set_state(PomodoroState::Work);
+            }
+            ["stop"] => self.stop(),
+            ["short"] => self.set_state(PomodoroState::ShortBreak),
+            ["long"] => self.set_state(PomodoroState::LongBreak),
+            ["next"] => self.next_state(),
+            ["pause"] => self.pause()?,
+            ["resume"] => self.resume(),
+            ["work", minutes] => self.set_work_duration(parse_minutes(minutes)?),
+            ["short", minutes] => self.set_short_break_duration(parse_minutes(minutes)?),
+            ["long", minutes] => self.set_long_break_duration(parse_minutes(minutes)?),
+            ["interval", count] => self.set_long_break_interval(parse_interval(count)?),
+            _ => {
+                return Err(i18n::tf(
+                    "pomodoro.unknown_command",
+                    &[("command", &command)],
+                ))
+            }
+        }
+        self.save();
+        Ok(())
+    }
+}
+
+// 控制命令可能来自 socket，分钟数过大或间隔为 0 都当作无效数字
+fn parse_minutes(value: &str) -> Result<Duration, String> {
+    value
+        .parse::<u64>()
+        .ok()
+        .and_then(|minutes| minutes.checked_mul(60))
+        .map(Duration::from_secs)
+        .ok_or_else(|| invalid_number(value))
+}
+
+fn parse_interval(value: &str) -> Result<u32, String> {
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|count| *count > 0)
+        .ok_or_else(|| invalid_number(value))
+}
+
+fn invalid_number(value: &str) -> String {
+    i18n::tf("pomodoro.invalid_number", &[("value", &value)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minutes(count: u64) -> Duration {
+        Duration::from_secs(count * 60)
+    }
+
+    // 把开始时间往前挪，模拟阶段已经进行了一段时间
+    fn started(state: PomodoroState, ago: Duration) -> PomodoroTimer {
+        let mut timer = PomodoroTimer::new(None);
+        timer.set_state(state);
+        timer.start_time = Instant::now().checked_sub(ago);
+        timer
+    }
+
+    fn remaining_minutes(timer: &PomodoroTimer) -> f64 {
+        timer.remaining_time().unwrap().as_secs_f64() / 60.0
+    }
+
+    #[test]
+    fn remaining_time_excludes_paused_time() {
+        let mut timer = started(PomodoroState::Work, minutes(10));
+        assert!((remaining_minutes(&timer) - 15.0).abs() < 0.1);
+
+        timer.paused_total = minutes(4);
+        assert!((remaining_minutes(&timer) - 19.0).abs() < 0.1);
+
+        // 暂停中的这段也不算
+        timer.paused_at = Instant::now().checked_sub(minutes(2));
+        assert!((remaining_minutes(&timer) - 21.0).abs() < 0.1);
+        timer.resume();
+        assert!(!timer.is_paused());
+        assert!((timer.paused_duration().as_secs_f64() / 60.0 - 6.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn repeated_pause_counts_once() {
+        let mut timer = started(PomodoroState::Work, minutes(1));
+        timer.pause().unwrap();
+        timer.pause().unwrap();
+        assert_eq!(timer.pause_count, 1);
+        timer.resume();
+        timer.resume();
+        timer.pause().unwrap();
+        assert_eq!(timer.pause_count, 2);
+
+        let mut idle = PomodoroTimer::new(None);
+        assert!(idle.pause().is_err());
+        assert_eq!(idle.pause_count, 0);
+    }
+
+    #[test]
+    fn phase_record_reports_paused_time() {
+        let mut timer = started(PomodoroState::Work, minutes(30));
+        timer.paused_total = minutes(5);
+        timer.pause_count = 2;
+        let record = timer.phase_record().unwrap();
+        assert_eq!(record.phase, PomodoroState::Work);
+        assert_eq!(record.pause_count, 2);
+        assert_eq!(record.paused_secs, 300);
+        assert_eq!(record.planned_secs, 1500);
+        assert_eq!(record.actual_secs, 1500);
+        assert!(record.completed);
+
+        let mut short = started(PomodoroState::ShortBreak, minutes(3));
+        short.paused_total = minutes(1);
+        let record = short.phase_record().unwrap();
+        assert_eq!(record.actual_secs / 60, 2);
+        assert!(!record.completed);
+
+        assert!(PomodoroTimer::new(None).phase_record().is_none());
+    }
+
+    fn cycling(confirm_work: bool) -> PomodoroTimer {
+        let mut timer = PomodoroTimer::new(None);
+        timer.auto_advance = true;
+        timer.confirm_work = confirm_work;
+        timer.set_long_break_interval(3);
+        timer.set_state(PomodoroState::Work);
+        timer
+    }
+
+    #[test]
+    fn long_break_after_every_interval() {
+        let mut timer = cycling(false);
+        let mut phases = Vec::new();
+        for _ in 0..8 {
+            timer.next_state();
+            phases.push(timer.state);
+        }
+        use PomodoroState::*;
+        assert_eq!(
+            phases,
+            [ShortBreak, Work, ShortBreak, Work, LongBreak, Work, ShortBreak, Work]
+        );
+        assert_eq!(timer.completed_work_sessions, 4);
+        assert!(timer.start_time.is_some());
+    }
+
+    #[test]
+    fn confirm_work_waits_after_breaks() {
+        let mut timer = cycling(true);
+        timer.next_state();
+        assert_eq!(timer.state, PomodoroState::ShortBreak);
+        timer.next_state();
+        assert_eq!(timer.state, PomodoroState::Idle);
+        assert!(timer.start_time.is_none());
+        // 确认之后继续按间隔计数
+        timer.set_state(PomodoroState::Work);
+        timer.next_state();
+        assert_eq!(timer.state, PomodoroState::ShortBreak);
+        assert_eq!(timer.completed_work_sessions, 2);
+    }
+
+    #[test]
+    fn manual_mode_stops_after_each_phase() {
+        let mut timer = cycling(false);
+        timer.auto_advance = false;
+        timer.next_state();
+        assert_eq!(timer.state, PomodoroState::Idle);
+        assert_eq!(timer.completed_work_sessions, 1);
+        assert_eq!(timer.following_state(), PomodoroState::Idle);
+    }
+}
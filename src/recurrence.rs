@@ -0,0 +1,373 @@
+use std::str::FromStr;
+
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, Timelike};
+
+use crate::config::{describe_duration, parse_duration};
+use crate::i18n;
+
+// 按天扫描的上限，足够覆盖闰年的 2 月 29 日等情况
+const MAX_SCAN_DAYS: u32 = 366 * 4 + 1;
+
+#[derive(Debug, Clone)]
+pub enum Repeat {
+    Daily,
+    Weekdays,
+    Weekly,
+    Monthly,
+    Yearly,
+    Cron(Box<cron::Schedule>),
+}
+
+impl FromStr for Repeat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "daily" => Ok(Repeat::Daily),
+            "weekdays" => Ok(Repeat::Weekdays),
+            "weekly" => Ok(Repeat::Weekly),
+            "monthly" => Ok(Repeat::Monthly),
+            "yearly" => Ok(Repeat::Yearly),
+            expr => {
+                // 标准 5 段 cron 表达式没有秒字段，补上 0 秒
+                let expr = if expr.split_whitespace().count() == 5 {
+                    format!("0 {expr}")
+                } else {
+                    expr.to_string()
+                };
+                cron::Schedule::from_str(&expr)
+                    .map(|schedule| Repeat::Cron(Box::new(schedule)))
+                    .map_err(|err| {
+                        i18n::tf(
+                            "config.invalid_repeat_rule",
+                            &[("rule", &s), ("error", &err)],
+                        )
+                    })
+            }
+        }
+    }
+}
+
+impl Repeat {
+    // 返回 base 起算的重复序列中第一个严格晚于 after 的时间点
+    pub fn next_after(&self, base: NaiveDateTime, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        if let Repeat::Cron(schedule) = self {
+            let after = after.max(base - Duration::seconds(1));
+            return schedule
+                .after(&after.and_utc())
+                .next()
+                .map(|datetime| datetime.naive_utc());
+        }
+        // 起点本身也要符合规则，例如 weekdays 的起点落在周六
+        let time = base.time();
+        let mut date = after.date().max(base.date());
+        for _ in 0..MAX_SCAN_DAYS {
+            let candidate = date.and_time(time);
+            if candidate > after && self.matches(base.date(), date) {
+                return Some(candidate);
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+
+    // 返回不晚于 until 的最近一次时间点
+    pub fn last_until(&self, base: NaiveDateTime, until: NaiveDateTime) -> Option<NaiveDateTime> {
+        if until < base {
+            return None;
+        }
+        if let Repeat::Cron(schedule) = self {
+            let until = until.with_nanosecond(0)? + Duration::seconds(1);
+            return schedule
+                .after(&until.and_utc())
+                .next_back()
+                .map(|datetime| datetime.naive_utc())
+                .filter(|datetime| *datetime >= base);
+        }
+
+        let time = base.time();
+        let mut date = until.date();
+        for _ in 0..MAX_SCAN_DAYS {
+            let candidate = date.and_time(time);
+            if candidate < base {
+                return None;
+            }
+            if candidate <= until && self.matches(base.date(), date) {
+                return Some(candidate);
+            }
+            date = date.pred_opt()?;
+        }
+        None
+    }
+
+    fn matches(&self, anchor: NaiveDate, date: NaiveDate) -> bool {
+        match self {
+            Repeat::Daily => true,
+            Repeat::Weekdays => date.weekday().num_days_from_monday() < 5,
+            Repeat::Weekly => date.weekday() == anchor.weekday(),
+            Repeat::Monthly => date.day() == anchor.day().min(days_in_month(date)),
+            Repeat::Yearly => {
+                date.month() == anchor.month()
+                    && date.day() == anchor.day().min(days_in_month(date))
+            }
+            Repeat::Cron(_) => false,
+        }
+    }
+}
+
+// 正计时的里程碑：经过固定时长触发一次，或者每隔若干天、若干月触发
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Milestone {
+    Once(Duration),
+    EveryDays(i64),
+    EveryMonths(u32),
+}
+
+impl FromStr for Milestone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || i18n::tf("config.invalid_milestone_format", &[("milestone", &s)]);
+        let input = s.trim().to_lowercase();
+        let (count, unit) = match input.split_whitespace().collect::<Vec<_>>().as_slice() {
+            ["every", unit] => (1, *unit),
+            ["every", count, unit] => (count.parse::<u32>().map_err(|_| invalid())?, *unit),
+            [duration] => {
+                return parse_duration(duration)
+                    .filter(|duration| *duration > Duration::zero())
+                    .map(Milestone::Once)
+                    .ok_or_else(invalid)
+            }
+            _ => return Err(invalid()),
+        };
+        if count == 0 {
+            return Err(invalid());
+        }
+        match unit.trim_end_matches('s') {
+            "day" => Ok(Milestone::EveryDays(count.into())),
+            "week" => Ok(Milestone::EveryDays(i64::from(count) * 7)),
+            "month" => Ok(Milestone::EveryMonths(count)),
+            "year" => count
+                .checked_mul(12)
+                .map(Milestone::EveryMonths)
+                .ok_or_else(invalid),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl Milestone {
+    // 从 origin 起第 count 个里程碑的时间，count 从 1 开始
+    pub fn nth(&self, origin: NaiveDateTime, count: i64) -> Option<NaiveDateTime> {
+        if count < 1 {
+            return None;
+        }
+        match self {
+            Milestone::Once(duration) if count == 1 => origin.checked_add_signed(*duration),
+            Milestone::Once(_) => None,
+            Milestone::EveryDays(days) => {
+                origin.checked_add_signed(Duration::try_days(days.checked_mul(count)?)?)
+            }
+            Milestone::EveryMonths(months) => {
+                let total = u32::try_from(count).ok()?.checked_mul(*months)?;
+                origin.checked_add_months(Months::new(total))
+            }
+        }
+    }
+
+    // 到 now 为止已经越过了几个里程碑
+    pub fn crossed(&self, origin: NaiveDateTime, now: NaiveDateTime) -> i64 {
+        if now < origin {
+            return 0;
+        }
+        match self {
+            Milestone::Once(_) => i64::from(self.nth(origin, 1).is_some_and(|at| at <= now)),
+            Milestone::EveryDays(days) => (now - origin).num_days() / days,
+            Milestone::EveryMonths(months) => {
+                let elapsed =
+                    (now.year() - origin.year()) * 12 + now.month() as i32 - origin.month() as i32;
+                let mut count = i64::from(elapsed.max(0)) / i64::from(*months);
+                // 月内的日期和时间还没到时退一个
+                while count > 0 && self.nth(origin, count).is_none_or(|at| at > now) {
+                    count -= 1;
+                }
+                count
+            }
+        }
+    }
+
+    // 第 count 个里程碑对应的经过时长，用于通知
+    pub fn describe(&self, count: i64) -> String {
+        match self {
+            Milestone::Once(duration) => describe_duration(*duration),
+            Milestone::EveryDays(days) => {
+                let days = days * count;
+                i18n::tfn("unit.day", days, &[("count", &days)])
+            }
+            Milestone::EveryMonths(months) => {
+                let months = i64::from(*months) * count;
+                if months % 12 == 0 {
+                    i18n::tfn("unit.year", months / 12, &[("count", &(months / 12))])
+                } else {
+                    i18n::tfn("unit.month", months, &[("count", &months)])
+                }
+            }
+        }
+    }
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .map(|first| (first - Duration::days(1)).day())
+        .unwrap_or(31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(input: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn repeat(rule: &str) -> Repeat {
+        rule.parse().unwrap()
+    }
+
+    #[test]
+    fn weekdays_skip_weekend() {
+        let base = at("2030-01-05 18:00:00"); // 周六
+        let weekdays = repeat("weekdays");
+        assert_eq!(
+            weekdays.next_after(base, at("2030-01-01 00:00:00")),
+            Some(at("2030-01-07 18:00:00"))
+        );
+        assert_eq!(
+            weekdays.next_after(base, at("2030-01-11 18:00:00")),
+            Some(at("2030-01-14 18:00:00"))
+        );
+    }
+
+    #[test]
+    fn monthly_clamps_to_month_end() {
+        let base = at("2024-01-31 09:00:00");
+        let monthly = repeat("monthly");
+        assert_eq!(
+            monthly.next_after(base, base),
+            Some(at("2024-02-29 09:00:00"))
+        );
+        assert_eq!(
+            monthly.next_after(base, at("2024-02-29 09:00:00")),
+            Some(at("2024-03-31 09:00:00"))
+        );
+        assert_eq!(
+            monthly.next_after(base, at("2024-04-01 00:00:00")),
+            Some(at("2024-04-30 09:00:00"))
+        );
+    }
+
+    #[test]
+    fn yearly_on_leap_day() {
+        let base = at("2024-02-29 08:00:00");
+        let yearly = repeat("yearly");
+        assert_eq!(
+            yearly.next_after(base, base),
+            Some(at("2025-02-28 08:00:00"))
+        );
+        assert_eq!(
+            yearly.next_after(base, at("2027-03-01 00:00:00")),
+            Some(at("2028-02-29 08:00:00"))
+        );
+    }
+
+    #[test]
+    fn five_field_cron() {
+        let base = at("2024-01-01 00:00:00");
+        let cron = repeat("30 9 * * MON-FRI");
+        assert_eq!(
+            cron.next_after(base, at("2024-01-05 10:00:00")),
+            Some(at("2024-01-08 09:30:00"))
+        );
+        assert_eq!(
+            cron.last_until(base, at("2024-01-07 12:00:00")),
+            Some(at("2024-01-05 09:30:00"))
+        );
+        assert!("61 * * * *".parse::<Repeat>().is_err());
+    }
+
+    fn milestone(rule: &str) -> Milestone {
+        rule.parse().unwrap()
+    }
+
+    #[test]
+    fn milestone_rules() {
+        assert_eq!(milestone("every year"), Milestone::EveryMonths(12));
+        assert_eq!(milestone("Every 2 weeks"), Milestone::EveryDays(14));
+        assert_eq!(milestone("500d"), Milestone::Once(Duration::days(500)));
+        for rule in [
+            "every 0 days",
+            "every fortnight",
+            "0d",
+            "999999999999d",
+            "soon",
+        ] {
+            assert!(rule.parse::<Milestone>().is_err(), "{rule}");
+        }
+    }
+
+    #[test]
+    fn monthly_milestones_clamp_to_month_end() {
+        let origin = at("2024-01-31 10:00:00");
+        let monthly = milestone("every month");
+        assert_eq!(monthly.nth(origin, 1), Some(at("2024-02-29 10:00:00")));
+        assert_eq!(monthly.nth(origin, 2), Some(at("2024-03-31 10:00:00")));
+        assert_eq!(monthly.crossed(origin, at("2024-02-29 09:59:59")), 0);
+        assert_eq!(monthly.crossed(origin, at("2024-02-29 10:00:00")), 1);
+        assert_eq!(monthly.crossed(origin, at("2024-03-30 23:00:00")), 1);
+        assert_eq!(
+            milestone("every year").crossed(origin, at("2026-01-31 10:00:00")),
+            2
+        );
+    }
+
+    #[test]
+    fn day_milestones() {
+        let origin = at("2016-09-26 00:00:00");
+        let thousand = milestone("every 1000 days");
+        assert_eq!(thousand.nth(origin, 1), Some(at("2019-06-23 00:00:00")));
+        assert_eq!(thousand.crossed(origin, at("2019-06-22 23:59:59")), 0);
+        assert_eq!(thousand.crossed(origin, at("2022-03-19 00:00:00")), 2);
+        assert_eq!(thousand.crossed(origin, at("2010-01-01 00:00:00")), 0);
+        assert_eq!(thousand.nth(origin, 0), None);
+        assert_eq!(thousand.nth(origin, i64::MAX), None);
+
+        let once = milestone("500d");
+        assert_eq!(once.nth(origin, 2), None);
+        assert_eq!(once.crossed(origin, at("2018-02-08 00:00:00")), 1);
+        assert_eq!(once.crossed(origin, at("2018-02-07 23:59:59")), 0);
+        let far = Milestone::Once(Duration::days(100_000_000));
+        assert_eq!(far.nth(origin, 1), None);
+        assert_eq!(far.crossed(origin, at("2024-01-01 00:00:00")), 0);
+    }
+
+    #[test]
+    fn last_until_respects_base() {
+        let base = at("2024-01-10 12:00:00");
+        let daily = repeat("daily");
+        assert_eq!(daily.last_until(base, at("2024-01-09 23:00:00")), None);
+        assert_eq!(daily.last_until(base, base), Some(base));
+        assert_eq!(
+            daily.last_until(base, at("2024-01-12 11:59:59")),
+            Some(at("2024-01-11 12:00:00"))
+        );
+        assert_eq!(
+            repeat("weekly").last_until(base, at("2024-01-20 00:00:00")),
+            Some(at("2024-01-17 12:00:00"))
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use std::path::Path;
+use std::time::Duration;
+
+use fs_notify::{Event, EventKind, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+
+use crate::config::{CountDownConfig, HotReload};
+
+// 编辑器保存时常有多次写入，安静这么久之后再加载
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+// 监视配置文件所在的目录而不是文件本身：编辑器常先写临时文件再改名覆盖，
+// 文件换了 inode 之后对原文件的监视就失效了
+pub fn watch(mut config: CountDownConfig) -> fs_notify::Result<()> {
+    let path = Path::new(config.file_name()).to_path_buf();
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => Path::new(".").to_path_buf(),
+    };
+    let name = path.file_name().map(|name| name.to_os_string());
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut watcher = fs_notify::recommended_watcher(move |event: fs_notify::Result<Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+        if event
+            .paths
+            .iter()
+            .any(|path| path.file_name() == name.as_deref())
+        {
+            let _ = tx.send(());
+        }
+    })?;
+    watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+
+    tokio::spawn(async move {
+        let _watcher = watcher;
+        while rx.recv().await.is_some() {
+            while let Ok(Some(())) = tokio::time::timeout(DEBOUNCE, rx.recv()).await {}
+            let _ = config.reload().await;
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::watch;
+
+use crate::config::{CountDownConfig, PomodoroConfig};
+use crate::engine::{self, Scheduled};
+use crate::i18n::{self, Lang};
+use crate::notify::Notifiers;
+use crate::state::FireLog;
+
+// 墙上时间可能跳变（休眠唤醒、手动改时间），最多睡这么久就重新检查一次
+pub const MAX_SLEEP: Duration = Duration::from_secs(1);
+
+pub fn until_next_second(now: DateTime<Utc>) -> Duration {
+    let nanos = now.timestamp_subsec_nanos().min(999_999_999);
+    Duration::from_secs(1) - Duration::from_nanos(nanos as u64)
+}
+
+// 缓存解析好的倒计时，只在配置变化或到了提醒、到点的时刻才重新计算
+pub struct Scheduler {
+    config: CountDownConfig,
+    changes: watch::Receiver<u64>,
+    notify_sound: Option<String>,
+    lang: Option<Lang>,
+    fire_log: FireLog,
+    stale: bool,
+    next_due: Option<DateTime<Utc>>,
+    pub notifiers: Notifiers,
+    pub pomodoro: PomodoroConfig,
+    pub scheduled: Vec<Scheduled>,
+    pub errors: Vec<String>,
+}
+
+impl Scheduler {
+    pub fn new(config: CountDownConfig, notify_sound: Option<String>, lang: Option<Lang>) -> Self {
+        Scheduler {
+            changes: config.subscribe(),
+            config,
+            notify_sound,
+            lang,
+            fire_log: FireLog::load(),
+            stale: true,
+            next_due: None,
+            notifiers: Notifiers::default(),
+            pomodoro: PomodoroConfig::default(),
+            scheduled: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    // 需要时重新解析配置，并发出到期的提醒
+    pub async fn update(&mut self, now: DateTime<Utc>) {
+        if self.changes.has_changed().unwrap_or(false) {
+            self.changes.borrow_and_update();
+            self.stale = true;
+        }
+        if !self.stale && self.next_due.is_none_or(|due| due > now) {
+            // 通知在后台线程发送，失败的原因随时可能出现
+            self.refresh_errors().await;
+            return;
+        }
+        let data = self.config.get_config().await;
+        i18n::set_lang(i18n::select(self.lang, data.lang));
+        if self.stale {
+            self.notifiers = Notifiers::from_config(&data.notify, self.notify_sound.clone());
+        }
+        self.pomodoro = data.pomodoro.clone();
+        self.scheduled = engine::schedule(data, now);
+        self.refresh_errors().await;
+        engine::fire_due(&mut self.fire_log, &self.notifiers, &self.scheduled, now);
+        self.next_due = engine::next_due(&self.scheduled, now);
+        self.stale = false;
+    }
+
+    async fn refresh_errors(&mut self) {
+        self.errors = self
+            .config
+            .reload_error()
+            .await
+            .map(|err| err.lines())
+            .unwrap_or_default();
+        self.errors.extend(self.notifiers.failure());
+    }
+
+    // 最多睡到下一个提醒或到点的时刻
+    pub fn wait_time(&self, now: DateTime<Utc>, wait: Duration) -> Duration {
+        let due = self
+            .next_due
+            .map(|due| (due - now).to_std().unwrap_or_default())
+            .unwrap_or(MAX_SLEEP);
+        wait.min(due).min(MAX_SLEEP)
+    }
+
+    pub async fn config_changed(&mut self) {
+        if self.changes.changed().await.is_err() {
+            // 配置不会再更新，只等其他事件
+            std::future::pending::<()>().await;
+        }
+        self.stale = true;
+    }
+
+    pub async fn sleep(&mut self, wait: Duration) {
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = self.config_changed() => {}
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::de::DeserializeOwned;
+use serde::Serialize as SerializeTrait;
+use serde_derive::{Deserialize, Serialize};
+
+const FIRED_FILE: &str = "fired.json";
+
+// 错过到点时刻（休眠、卡顿）后仍补发通知的时间窗口
+fn catch_up_window() -> Duration {
+    Duration::minutes(5)
+}
+
+pub fn state_dir() -> PathBuf {
+    // 测试不能读写用户真实的状态文件
+    if cfg!(test) {
+        return std::env::temp_dir().join(format!("countdown_cli-test-{}", std::process::id()));
+    }
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("countdown_cli")
+}
+
+pub fn load_json<T: DeserializeOwned>(name: &str) -> Option<T> {
+    read_json(&state_dir().join(name))
+}
+
+pub fn save_json<T: SerializeTrait>(
+    name: &str,
+    value: &T,
+) -> Result<(), Box<dyn std::error::Error>> {
+    write_json(&state_dir().join(name), value)
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+fn write_json<T: SerializeTrait>(path: &Path, value: &T) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    // 先写临时文件再改名，避免中途退出留下半个文件
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, serde_json::to_string_pretty(value)?)?;
+    std::fs::rename(tmp, path)?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct FiredEntry {
+    key: String,
+    due: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FiredState {
+    fired: Vec<FiredEntry>,
+}
+
+pub struct FireLog {
+    path: PathBuf,
+    state: FiredState,
+}
+
+impl FireLog {
+    pub fn load() -> Self {
+        Self::open(state_dir().join(FIRED_FILE))
+    }
+
+    pub fn open(path: PathBuf) -> Self {
+        FireLog {
+            state: read_json(&path).unwrap_or_default(),
+            path,
+        }
+    }
+
+    // 到点且未通知过时返回 true，并记录下来保证只通知一次
+    pub fn fire_once(&mut self, key: &str, due: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        if due > now || now - due > catch_up_window() {
+            return false;
+        }
+        let entry = FiredEntry {
+            key: key.to_string(),
+            due,
+        };
+        if self.state.fired.contains(&entry) {
+            return false;
+        }
+        self.state
+            .fired
+            .retain(|fired| now - fired.due <= catch_up_window());
+        self.state.fired.push(entry);
+        let _ = write_json(&self.path, &self.state);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(input: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(input)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    // 每个测试用自己的文件，测试并行运行时互不干扰
+    fn temp_log(name: &str) -> PathBuf {
+        let path = std::env::temp_dir()
+            .join(format!("countdown_cli-test-{}", std::process::id()))
+            .join(name);
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn fires_once_per_title_and_target() {
+        let mut log = FireLog::open(temp_log("once.json"));
+        let due = at("2025-01-01T12:00:00Z");
+        assert!(!log.fire_once("a", due, at("2025-01-01T11:59:59Z")));
+        assert!(log.fire_once("a", due, due));
+        assert!(!log.fire_once("a", due, at("2025-01-01T12:00:01Z")));
+        assert!(log.fire_once("b", due, at("2025-01-01T12:00:01Z")));
+        // 重复事件的下一次是新的目标时间
+        let next = at("2025-01-02T12:00:00Z");
+        assert!(log.fire_once("a", next, next));
+    }
+
+    #[test]
+    fn catches_up_on_skipped_seconds_within_window() {
+        let mut log = FireLog::open(temp_log("catch_up.json"));
+        let due = at("2025-01-01T12:00:00Z");
+        assert!(log.fire_once("late", due, at("2025-01-01T12:00:03Z")));
+        assert!(!log.fire_once("stale", due, at("2025-01-01T12:05:01Z")));
+    }
+
+    #[test]
+    fn does_not_refire_after_reload() {
+        let path = temp_log("reload.json");
+        let due = at("2025-01-01T12:00:00Z");
+        assert!(FireLog::open(path.clone()).fire_once("a", due, due));
+        let mut reloaded = FireLog::open(path.clone());
+        assert!(!reloaded.fire_once("a", due, at("2025-01-01T12:00:02Z")));
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,163 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Duration, Local, Utc};
+
+use crate::config::describe_duration;
+use crate::humanize;
+
+const TARGET_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Piece<'a> {
+    Text(&'a str),
+    // {name} 或 {name:格式}
+    Field(&'a str, Option<&'a str>),
+}
+
+pub fn parse(template: &str) -> Vec<Piece<'_>> {
+    let mut pieces = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}').map(|close| open + close) else {
+            break;
+        };
+        if open > 0 {
+            pieces.push(Piece::Text(&rest[..open]));
+        }
+        let field = &rest[open + 1..close];
+        pieces.push(match field.split_once(':') {
+            Some((name, spec)) => Piece::Field(name, Some(spec)),
+            None => Piece::Field(field, None),
+        });
+        rest = &rest[close + 1..];
+    }
+    if !rest.is_empty() {
+        pieces.push(Piece::Text(rest));
+    }
+    pieces
+}
+
+const FIELDS: &[&str] = &[
+    "title", "weeks", "days", "hh", "mm", "ss", "ms", "seconds", "clock", "human", "relative",
+    "target",
+];
+
+// 找出模板里无法识别的变量，validate 用
+pub fn unknown_fields(template: &str) -> Vec<String> {
+    parse(template)
+        .into_iter()
+        .filter_map(|piece| match piece {
+            Piece::Field(name, _) if !FIELDS.contains(&name) => Some(name.to_string()),
+            Piece::Field("target", Some(spec))
+                if StrftimeItems::new(spec).any(|item| matches!(item, Item::Error)) =>
+            {
+                Some(format!("target:{spec}"))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+// 倒计时模板可用的变量，已过去的事件按绝对值计算
+pub struct CountdownValues<'a> {
+    pub title: &'a str,
+    pub target: DateTime<Utc>,
+    pub remaining: Duration,
+    // {clock} 是否带毫秒
+    pub with_ms: bool,
+}
+
+impl CountdownValues<'_> {
+    pub fn get(&self, name: &str, spec: Option<&str>) -> Option<String> {
+        let remaining = self.remaining.abs();
+        Some(match name {
+            "title" => self.title.to_string(),
+            "weeks" => remaining.num_weeks().to_string(),
+            "days" => remaining.num_days().to_string(),
+            "hh" => format!("{:02}", remaining.num_hours() % 24),
+            "mm" => format!("{:02}", remaining.num_minutes() % 60),
+            "ss" => format!("{:02}", remaining.num_seconds() % 60),
+            "ms" => format!("{:03}", remaining.num_milliseconds() % 1000),
+            "seconds" => remaining.num_seconds().to_string(),
+            "clock" if self.with_ms => format!(
+                "{:02}:{:02}:{:02}.{:03}",
+                remaining.num_hours() % 24,
+                remaining.num_minutes() % 60,
+                remaining.num_seconds() % 60,
+                remaining.num_milliseconds() % 1000
+            ),
+            "clock" => format!(
+                "{:02}:{:02}:{:02}",
+                remaining.num_hours() % 24,
+                remaining.num_minutes() % 60,
+                remaining.num_seconds() % 60
+            ),
+            "human" => describe_duration(remaining),
+            "relative" => humanize::relative(self.target, self.target - self.remaining),
+            "target" => {
+                // 无效的格式串在 Display 时会 panic，先检查
+                let spec = spec.unwrap_or(TARGET_FORMAT);
+                if StrftimeItems::new(spec).any(|item| matches!(item, Item::Error)) {
+                    return None;
+                }
+                self.target.with_timezone(&Local).format(spec).to_string()
+            }
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_splits_text_and_fields() {
+        assert_eq!(
+            parse("{title}: {days}d left ({target:%H:%M})"),
+            vec![
+                Piece::Field("title", None),
+                Piece::Text(": "),
+                Piece::Field("days", None),
+                Piece::Text("d left ("),
+                Piece::Field("target", Some("%H:%M")),
+                Piece::Text(")"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unclosed_brace_stays_text() {
+        assert_eq!(
+            parse("{title} {oops"),
+            vec![Piece::Field("title", None), Piece::Text(" {oops")]
+        );
+        assert_eq!(parse(""), vec![]);
+    }
+
+    #[test]
+    fn unknown_fields_reports_names_and_bad_specs() {
+        assert!(unknown_fields("{title} {weeks} {clock} {relative} {target:%Y}").is_empty());
+        assert_eq!(
+            unknown_fields("{title} {dayz} {target:%Q} {target:%Y}"),
+            vec!["dayz".to_string(), "target:%Q".to_string()]
+        );
+    }
+
+    #[test]
+    fn values_use_absolute_remaining_time() {
+        let target = DateTime::parse_from_rfc3339("2025-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let values = CountdownValues {
+            title: "t",
+            target,
+            remaining: -(Duration::days(9) + Duration::milliseconds(3_723_045)),
+            with_ms: true,
+        };
+        assert_eq!(values.get("weeks", None).as_deref(), Some("1"));
+        assert_eq!(values.get("days", None).as_deref(), Some("9"));
+        assert_eq!(values.get("clock", None).as_deref(), Some("01:02:03.045"));
+        assert_eq!(values.get("target", Some("%Q")), None);
+        assert_eq!(values.get("nope", None), None);
+    }
+}
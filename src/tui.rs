@@ -0,0 +1,261 @@
+use std::io::{stdout, Stdout, Write};
+
+use crossterm::style::{Attribute, Color, Print, SetAttribute, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, ExecutableCommand, QueueableCommand};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+// 宽字符占两列，第二列用这个占位，绘制时跳过
+const CONTINUATION: char = '\0';
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bold: bool,
+    pub reverse: bool,
+}
+
+impl Style {
+    pub fn fg(color: Color) -> Self {
+        Style {
+            fg: Some(color),
+            ..Style::default()
+        }
+    }
+
+    pub fn bold(self) -> Self {
+        Style { bold: true, ..self }
+    }
+
+    pub fn reverse(self) -> Self {
+        Style {
+            reverse: true,
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+}
+
+impl Span {
+    pub fn raw(text: impl Into<String>) -> Self {
+        Span {
+            text: text.into(),
+            style: Style::default(),
+        }
+    }
+
+    pub fn styled(text: impl Into<String>, style: Style) -> Self {
+        Span {
+            text: text.into(),
+            style,
+        }
+    }
+}
+
+pub type Line = Vec<Span>;
+
+pub fn line_width(line: &Line) -> usize {
+    line.iter().map(|span| span.text.width()).sum()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Cell {
+    symbol: char,
+    style: Style,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell {
+            symbol: ' ',
+            style: Style::default(),
+        }
+    }
+}
+
+pub struct Buffer {
+    pub width: u16,
+    pub height: u16,
+    cells: Vec<Cell>,
+}
+
+impl Buffer {
+    pub fn new(width: u16, height: u16) -> Self {
+        Buffer {
+            width,
+            height,
+            cells: vec![Cell::default(); width as usize * height as usize],
+        }
+    }
+
+    fn index(&self, x: u16, y: u16) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+
+    // 从 (x, y) 开始写入文字，到 max_x 截断，返回写完后的列
+    pub fn put_str(&mut self, x: u16, y: u16, text: &str, style: Style, max_x: u16) -> u16 {
+        let max_x = max_x.min(self.width);
+        if y >= self.height {
+            return x;
+        }
+        let mut x = x;
+        for symbol in text.chars() {
+            let width = symbol.width().unwrap_or(0) as u16;
+            if width == 0 {
+                continue;
+            }
+            if x + width > max_x {
+                break;
+            }
+            let index = self.index(x, y);
+            self.cells[index] = Cell { symbol, style };
+            if width == 2 {
+                self.cells[index + 1] = Cell {
+                    symbol: CONTINUATION,
+                    style,
+                };
+            }
+            x += width;
+        }
+        x
+    }
+
+    pub fn put_line(&mut self, x: u16, y: u16, line: &Line, max_x: u16) -> u16 {
+        line.iter().fold(x, |x, span| {
+            self.put_str(x, y, &span.text, span.style, max_x)
+        })
+    }
+
+    // 用给定样式填满一行，状态栏用
+    pub fn fill_row(&mut self, y: u16, style: Style) {
+        let width = self.width;
+        self.put_str(0, y, &" ".repeat(width as usize), style, width);
+    }
+
+    pub fn draw_box(&mut self, x: u16, y: u16, width: u16, height: u16, title: &str) {
+        if width < 2 || height < 2 {
+            return;
+        }
+        let right = x + width - 1;
+        let bottom = y + height - 1;
+        let border = Style::fg(Color::DarkGrey);
+        let horizontal = "─".repeat(width as usize - 2);
+        self.put_str(x, y, &format!("┌{horizontal}┐"), border, right + 1);
+        self.put_str(x, bottom, &format!("└{horizontal}┘"), border, right + 1);
+        for row in y + 1..bottom {
+            self.put_str(x, row, "│", border, right + 1);
+            self.put_str(right, row, "│", border, right + 1);
+        }
+        if !title.is_empty() {
+            self.put_str(
+                x + 2,
+                y,
+                &format!(" {title} "),
+                Style::default().bold(),
+                right - 1,
+            );
+        }
+    }
+}
+
+// 全屏终端：进入备用屏幕和 raw 模式，退出时恢复
+pub struct Terminal {
+    stdout: Stdout,
+    front: Option<Buffer>,
+}
+
+impl Terminal {
+    pub fn enter() -> std::io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        let mut stdout = stdout();
+        stdout.execute(EnterAlternateScreen)?;
+        stdout.execute(cursor::Hide)?;
+        stdout.execute(Clear(ClearType::All))?;
+        Ok(Terminal {
+            stdout,
+            front: None,
+        })
+    }
+
+    pub fn size() -> (u16, u16) {
+        terminal::size().unwrap_or((80, 24))
+    }
+
+    // 尺寸变化后整屏重画
+    pub fn invalidate(&mut self) {
+        self.front = None;
+    }
+
+    // 只输出和上一帧不同的单元格
+    pub fn draw(&mut self, back: Buffer, cursor_at: Option<(u16, u16)>) -> std::io::Result<()> {
+        let full = self
+            .front
+            .as_ref()
+            .is_none_or(|front| front.width != back.width || front.height != back.height);
+        if full {
+            self.stdout.queue(SetAttribute(Attribute::Reset))?;
+            self.stdout.queue(Clear(ClearType::All))?;
+        }
+
+        let mut position: Option<(u16, u16)> = None;
+        let mut current: Option<Style> = None;
+        for y in 0..back.height {
+            for x in 0..back.width {
+                let index = back.index(x, y);
+                let cell = &back.cells[index];
+                if cell.symbol == CONTINUATION {
+                    continue;
+                }
+                if !full && self.front.as_ref().map(|front| &front.cells[index]) == Some(cell) {
+                    continue;
+                }
+                if position != Some((x, y)) {
+                    self.stdout.queue(cursor::MoveTo(x, y))?;
+                }
+                if current != Some(cell.style) {
+                    self.stdout.queue(SetAttribute(Attribute::Reset))?;
+                    if let Some(color) = cell.style.fg {
+                        self.stdout.queue(SetForegroundColor(color))?;
+                    }
+                    if cell.style.bold {
+                        self.stdout.queue(SetAttribute(Attribute::Bold))?;
+                    }
+                    if cell.style.reverse {
+                        self.stdout.queue(SetAttribute(Attribute::Reverse))?;
+                    }
+                    current = Some(cell.style);
+                }
+                self.stdout.queue(Print(cell.symbol))?;
+                position = Some((x + cell.symbol.width().unwrap_or(1) as u16, y));
+            }
+        }
+        self.stdout.queue(SetAttribute(Attribute::Reset))?;
+
+        match cursor_at {
+            Some((x, y)) => {
+                self.stdout.queue(cursor::MoveTo(x, y))?;
+                self.stdout.queue(cursor::Show)?;
+            }
+            None => {
+                self.stdout.queue(cursor::Hide)?;
+            }
+        }
+        self.stdout.flush()?;
+        self.front = Some(back);
+        Ok(())
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = self.stdout.execute(SetAttribute(Attribute::Reset));
+        let _ = self.stdout.execute(cursor::Show);
+        let _ = self.stdout.execute(LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}